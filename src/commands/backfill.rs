@@ -0,0 +1,112 @@
+use crate::commands;
+use crate::core::backfill::MAX_BACKFILL_COUNT;
+use crate::core::publisher::Publisher;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::scraper::Scraper;
+use anyhow::Result;
+use tracing::instrument;
+use tracing::{error, info};
+
+use super::*;
+
+/// Post a subscribed user's most recent reviews to the channel
+#[instrument(skip(ctx))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    default_member_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn backfill(
+    ctx: commands::Context<'_>,
+    #[description = "Backloggd RSS feed URL the channel is subscribed to"] feed_url: Option<String>,
+    #[description = "Username of the subscribed Backloggd user"] username: Option<String>,
+    #[description = "Number of recent reviews to post"]
+    #[min = 1]
+    #[max = 20]
+    count: u8,
+) -> Result<(), commands::Error> {
+    let channel_id = ctx.channel_id().get();
+
+    let sub_request = SubRequest {
+        feed_url,
+        username,
+        channel_id: &channel_id,
+    };
+
+    // Posting several reviews takes longer than Discord waits for a response
+    ctx.defer().await?;
+
+    let backfill_response = run_backfill(ctx, &sub_request, count).await;
+
+    match backfill_response {
+        Ok(posted) => {
+            info!({ action = "backfill-success", sub_request = ?sub_request, posted = posted }, "Successfully backfilled reviews");
+            if posted == 0 {
+                let _ = ctx
+                    .say("There are no recent reviews that haven't already been posted")
+                    .await?;
+            } else {
+                let _ = ctx.say(format!("Posted {} recent reviews", posted)).await?;
+            }
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "backfill-error", sub_request = ?sub_request, error = ?error }, "Error backfilling reviews");
+            say_subscription_error(ctx, &error).await?;
+            return Err(error.into());
+        }
+    };
+}
+
+/// Builds a publisher for the command's channel and posts up to `count` recent reviews.
+pub async fn run_backfill(
+    ctx: commands::Context<'_>,
+    sub_request: &SubRequest<'_>,
+    count: u8,
+) -> Result<usize, SubscriptionError> {
+    let publisher = Publisher::new(
+        ctx.data().scraper.clone(),
+        SqliteRepository {},
+        ctx.serenity_context().http.clone(),
+        ctx.data().cipher.clone(),
+    );
+
+    let backfill_handler = BackfillHandler::new(SqliteRepository {}, publisher);
+
+    backfill_handler
+        .handle_backfill(sub_request, count.min(MAX_BACKFILL_COUNT))
+        .await
+}
+
+pub struct BackfillHandler<R: Repository, S: Scraper> {
+    repository: R,
+    publisher: Publisher<S, R>,
+}
+
+impl<T: Repository, U: Scraper> BackfillHandler<T, U> {
+    fn new(repository: T, publisher: Publisher<U, T>) -> Self {
+        return Self {
+            repository,
+            publisher,
+        };
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_backfill(
+        &self,
+        request: &SubRequest<'_>,
+        count: u8,
+    ) -> Result<usize, SubscriptionError> {
+        info!("handling backfill command");
+
+        let (feed_url, sub) = find_subscription(&self.repository, request).await?;
+
+        let posted = self
+            .publisher
+            .backfill(&feed_url, &sub, count.into())
+            .await?;
+
+        Ok(posted)
+    }
+}
@@ -0,0 +1,169 @@
+use crate::commands;
+use crate::core::compare;
+use crate::core::compare::{GameRating, SharedGame};
+use crate::core::models::ReviewQuery;
+use crate::core::parser;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::scraper::FeedStatus;
+use crate::core::scraper::Scraper;
+use crate::core::validator;
+use anyhow::Result;
+use poise::CreateReply;
+use std::collections::HashMap;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, Error)]
+pub enum CompareError {
+    #[error("The given username {0} is not valid")]
+    InvalidUsername(String),
+    #[error("No Backloggd user has the username {0}")]
+    UserDoesNotExist(String),
+    #[error("Both usernames are the same")]
+    SameUser,
+    #[error("Backloggd is rate limiting requests")]
+    RateLimited,
+    #[error("Backloggd could not be reached")]
+    BackloggdUnavailable,
+    #[error("Unexpected internal error arose while comparing users")]
+    InternalError(#[from] anyhow::Error),
+}
+
+/// Compare two Backloggd users' ratings of the games they both reviewed
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn compare(
+    ctx: commands::Context<'_>,
+    #[description = "Username of the first Backloggd user"] user1: String,
+    #[description = "Username of the second Backloggd user"] user2: String,
+) -> Result<(), commands::Error> {
+    // Both users' feeds are scraped
+    ctx.defer().await?;
+
+    let repo = SqliteRepository {};
+
+    let scraper = ctx.data().scraper.clone();
+
+    let compare_handler = CompareHandler::new(repo, scraper);
+    let compare_response = compare_handler.handle_compare(&user1, &user2).await;
+
+    match compare_response {
+        Ok(shared) => {
+            info!({ action = "compare-success", user1 = user1, user2 = user2, shared = shared.len() }, "Successfully compared users");
+            let embed = compare::build_compare_embed(user1.trim(), user2.trim(), &shared);
+            ctx.send(CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "compare-error", user1 = user1, user2 = user2, error = ?error }, "Error comparing users");
+            match &error {
+                CompareError::InvalidUsername(username) => {
+                    let _ = ctx
+                        .say(format!("The username {} is invalid", username))
+                        .await?;
+                }
+                CompareError::UserDoesNotExist(username) => {
+                    let _ = ctx
+                        .say(format!("No Backloggd user has the username {}", username))
+                        .await?;
+                }
+                CompareError::SameUser => {
+                    let _ = ctx.say("Provide two different usernames").await?;
+                }
+                CompareError::RateLimited => {
+                    let _ = ctx
+                        .say(commands::feed_status_message(FeedStatus::RateLimited))
+                        .await?;
+                }
+                CompareError::BackloggdUnavailable => {
+                    let _ = ctx
+                        .say(commands::feed_status_message(FeedStatus::Unavailable))
+                        .await?;
+                }
+                CompareError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct CompareHandler<R: Repository, S: Scraper> {
+    repository: R,
+    scraper: S,
+}
+
+impl<T: Repository, U: Scraper> CompareHandler<T, U> {
+    fn new(repository: T, scraper: U) -> Self {
+        return Self {
+            repository,
+            scraper,
+        };
+    }
+
+    /// Returns the games both users rated, the biggest disagreements first.
+    #[instrument(skip(self))]
+    async fn handle_compare(
+        &self,
+        first_username: &str,
+        second_username: &str,
+    ) -> Result<Vec<SharedGame>, CompareError> {
+        info!("handling compare command");
+
+        let first_username = first_username.trim();
+        let second_username = second_username.trim();
+
+        if first_username.eq_ignore_ascii_case(second_username) {
+            return Err(CompareError::SameUser);
+        }
+
+        // Ratings come from stored reviews when the user's feed is polled, and from their feed
+        // otherwise
+        let first_ratings = self.get_ratings(first_username).await?;
+        let second_ratings = self.get_ratings(second_username).await?;
+
+        Ok(compare::compare_ratings(&first_ratings, &second_ratings))
+    }
+
+    async fn get_ratings(
+        &self,
+        username: &str,
+    ) -> Result<HashMap<String, GameRating>, CompareError> {
+        if validator::validate_username(username).is_err() {
+            return Err(CompareError::InvalidUsername(username.to_string()));
+        }
+
+        let feed_url = commands::feed_url_for_username(username);
+
+        let content = match self.scraper.fetch_feed(&feed_url).await {
+            Ok(content) => content,
+            Err(FeedStatus::NotFound) => {
+                return Err(CompareError::UserDoesNotExist(username.to_string()));
+            }
+            Err(FeedStatus::RateLimited) => return Err(CompareError::RateLimited),
+            Err(FeedStatus::Unavailable) => return Err(CompareError::BackloggdUnavailable),
+        };
+
+        // Stored reviews go back further than the feed, which only has the latest reviews
+        let stored = match self.repository.get_feed_id(&feed_url).await? {
+            Some(feed_id) => {
+                self.repository
+                    .find_reviews(&ReviewQuery {
+                        feed_ids: Some(vec![feed_id]),
+                        ..Default::default()
+                    })
+                    .await?
+            }
+            None => vec![],
+        };
+
+        let rss_feed = parser::parse_rss_xml(&content)?;
+
+        Ok(compare::collect_ratings(&stored, &rss_feed.channel.item))
+    }
+}
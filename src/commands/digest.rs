@@ -0,0 +1,301 @@
+use crate::commands;
+use crate::core::converter;
+use crate::core::digest;
+use crate::core::models::{DigestFrequency, DigestSchedule};
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use anyhow::Result;
+use chrono::{NaiveDateTime, Weekday};
+use chrono_tz::Tz;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum DeliveryMode {
+    #[name = "Immediate"]
+    Immediate,
+    #[name = "Daily digest"]
+    Daily,
+    #[name = "Weekly digest"]
+    Weekly,
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum DigestDay {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl From<&DigestDay> for Weekday {
+    fn from(day: &DigestDay) -> Self {
+        match day {
+            DigestDay::Monday => Weekday::Mon,
+            DigestDay::Tuesday => Weekday::Tue,
+            DigestDay::Wednesday => Weekday::Wed,
+            DigestDay::Thursday => Weekday::Thu,
+            DigestDay::Friday => Weekday::Fri,
+            DigestDay::Saturday => Weekday::Sat,
+            DigestDay::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DigestRequest<'a> {
+    channel_id: &'a u64,
+    mode: DeliveryMode,
+    time: Option<String>,
+    timezone: Option<String>,
+    weekday: Option<DigestDay>,
+}
+
+#[derive(Debug, Error)]
+pub enum DigestError {
+    #[error("The given time is not valid")]
+    InvalidTime,
+    #[error("The given timezone is not valid")]
+    InvalidTimezone,
+    #[error("Unable to find the next time to post the digest")]
+    Unschedulable,
+    #[error("Unexpected internal error arose while saving digest settings")]
+    InternalError(#[from] anyhow::Error),
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    default_member_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn digest(
+    ctx: commands::Context<'_>,
+    #[description = "Post reviews as they're found, or collect them into a scheduled digest"]
+    mode: DeliveryMode,
+    #[description = "Time of day to post the digest (defaults to 18:00)"] time: Option<String>,
+    #[description = "Timezone for the digest time, e.g. Europe/London (defaults to UTC)"]
+    timezone: Option<String>,
+    #[description = "Day to post weekly digests (defaults to Monday)"] weekday: Option<DigestDay>,
+) -> Result<(), commands::Error> {
+    let channel_id = ctx.channel_id().get();
+
+    let digest_request = DigestRequest {
+        channel_id: &channel_id,
+        mode,
+        time,
+        timezone,
+        weekday,
+    };
+
+    let repo = SqliteRepository {};
+    let digest_handler = DigestHandler::new(repo);
+    let digest_response = digest_handler.handle_digest(&digest_request).await;
+
+    match digest_response {
+        Ok(schedule_option) => {
+            info!({ action = "digest-success", digest_request = ?digest_request }, "Successfully updated channel delivery mode");
+            match schedule_option {
+                Some(schedule) => {
+                    let _ = ctx.say(describe_schedule(&schedule)).await?;
+                }
+                None => {
+                    let _ = ctx
+                        .say("Reviews will be posted to this channel as soon as they're found")
+                        .await?;
+                }
+            }
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "digest-error", digest_request = ?digest_request, error = ?error }, "Error updating channel delivery mode");
+            match error {
+                DigestError::InvalidTime => {
+                    let _ = ctx
+                        .say("The time you provided is invalid, use 24 hour HH:MM like 18:00")
+                        .await?;
+                }
+                DigestError::InvalidTimezone => {
+                    let _ = ctx
+                        .say("The timezone you provided is invalid, use a name like Europe/London")
+                        .await?;
+                }
+                DigestError::Unschedulable | DigestError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+fn describe_schedule(schedule: &DigestSchedule) -> String {
+    let time = schedule.time.format("%H:%M");
+
+    match (schedule.frequency, schedule.weekday) {
+        (DigestFrequency::Weekly, Some(weekday)) => format!(
+            "Reviews will be posted to this channel as a weekly digest every {} at {} ({})",
+            weekday,
+            time,
+            schedule.timezone.name()
+        ),
+        _ => format!(
+            "Reviews will be posted to this channel as a daily digest at {} ({})",
+            time,
+            schedule.timezone.name()
+        ),
+    }
+}
+
+pub struct DigestHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> DigestHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_digest(
+        &self,
+        request: &DigestRequest<'_>,
+    ) -> Result<Option<DigestSchedule>, DigestError> {
+        info!("handling digest command");
+
+        let frequency = match request.mode {
+            DeliveryMode::Immediate => {
+                self.repository
+                    .delete_digest_schedule(request.channel_id)
+                    .await?;
+                return Ok(None);
+            }
+            DeliveryMode::Daily => DigestFrequency::Daily,
+            DeliveryMode::Weekly => DigestFrequency::Weekly,
+        };
+
+        let schedule = build_digest_schedule(request, frequency, chrono::Utc::now().naive_utc())?;
+
+        self.repository.save_digest_schedule(&schedule).await?;
+
+        return Ok(Some(schedule));
+    }
+}
+
+fn build_digest_schedule(
+    request: &DigestRequest,
+    frequency: DigestFrequency,
+    now: NaiveDateTime,
+) -> Result<DigestSchedule, DigestError> {
+    let time = converter::parse_digest_time(request.time.as_deref().unwrap_or("18:00"))
+        .map_err(|_| DigestError::InvalidTime)?;
+
+    let timezone: Tz = request
+        .timezone
+        .as_deref()
+        .unwrap_or("UTC")
+        .trim()
+        .parse()
+        .map_err(|_| DigestError::InvalidTimezone)?;
+
+    let weekday = match frequency {
+        DigestFrequency::Daily => None,
+        DigestFrequency::Weekly => Some(
+            request
+                .weekday
+                .as_ref()
+                .map(Weekday::from)
+                .unwrap_or(Weekday::Mon),
+        ),
+    };
+
+    let mut schedule = DigestSchedule {
+        channel_id: *request.channel_id,
+        frequency,
+        weekday,
+        time,
+        timezone,
+        next_run: now,
+    };
+
+    schedule.next_run = digest::next_run(&schedule, now).ok_or(DigestError::Unschedulable)?;
+
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn build_request(
+        time: Option<&str>,
+        timezone: Option<&str>,
+        weekday: Option<DigestDay>,
+    ) -> DigestRequest<'static> {
+        DigestRequest {
+            channel_id: &0,
+            mode: DeliveryMode::Weekly,
+            time: time.map(|value| value.to_string()),
+            timezone: timezone.map(|value| value.to_string()),
+            weekday,
+        }
+    }
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 10)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn build_digest_schedule_uses_defaults_when_options_none() {
+        let request = build_request(None, None, None);
+
+        let actual = build_digest_schedule(&request, DigestFrequency::Weekly, now()).unwrap();
+
+        assert_eq!(actual.time.format("%H:%M").to_string(), "18:00");
+        assert_eq!(actual.timezone, chrono_tz::UTC);
+        assert_eq!(actual.weekday, Some(Weekday::Mon));
+        assert!(actual.next_run > now());
+    }
+
+    #[test]
+    fn build_digest_schedule_ignores_weekday_when_daily() {
+        let request = build_request(
+            Some("09:30"),
+            Some("America/New_York"),
+            Some(DigestDay::Friday),
+        );
+
+        let actual = build_digest_schedule(&request, DigestFrequency::Daily, now()).unwrap();
+
+        assert_eq!(actual.weekday, None);
+        assert_eq!(actual.timezone, chrono_tz::America::New_York);
+    }
+
+    #[test]
+    fn build_digest_schedule_returns_error_when_time_invalid() {
+        let request = build_request(Some("6pm"), None, None);
+
+        let actual = build_digest_schedule(&request, DigestFrequency::Daily, now());
+
+        assert!(matches!(actual, Err(DigestError::InvalidTime)));
+    }
+
+    #[test]
+    fn build_digest_schedule_returns_error_when_timezone_invalid() {
+        let request = build_request(None, Some("Mars/Olympus_Mons"), None);
+
+        let actual = build_digest_schedule(&request, DigestFrequency::Daily, now());
+
+        assert!(matches!(actual, Err(DigestError::InvalidTimezone)));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::commands;
+use crate::core::embed;
+use crate::core::scraper;
+use crate::core::scraper::Scraper;
+use crate::core::scraper::{GameDetails, GameSearchResult};
+use anyhow::Result;
+use poise::serenity_prelude::{
+    ComponentInteractionCollector, ComponentInteractionDataKind, CreateActionRow,
+    CreateInteractionResponse, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use poise::CreateReply;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+// Discord allows at most 25 options in a select menu
+const MAX_GAME_OPTIONS: usize = 25;
+
+#[derive(Debug, Error)]
+pub enum GameError {
+    #[error("No games matched the search")]
+    NoResults,
+    #[error("The chosen game page could not be read")]
+    NotFound,
+    #[error("Unexpected internal error arose while searching games")]
+    InternalError(#[from] anyhow::Error),
+}
+
+pub enum GameSearch {
+    Found(Box<GameDetails>),
+    // More than one game could be meant, so the user has to pick
+    Ambiguous(Vec<GameSearchResult>),
+}
+
+/// Look up a game on Backloggd
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn game(
+    ctx: commands::Context<'_>,
+    #[description = "Title of the game to search for"] title: String,
+) -> Result<(), commands::Error> {
+    // Searching and scraping the game page takes longer than Discord waits for a response
+    ctx.defer().await?;
+
+    let scraper = ctx.data().scraper.clone();
+
+    let game_handler = GameHandler::new(scraper);
+    let game_response = game_handler.handle_game(&title).await;
+
+    let results = match game_response {
+        Ok(GameSearch::Found(game)) => {
+            info!({ action = "game-success", title = title, game = game.url }, "Successfully found game");
+            ctx.send(CreateReply::default().embed(embed::build_game_embed(&game)))
+                .await?;
+            return Ok(());
+        }
+        Ok(GameSearch::Ambiguous(results)) => results,
+        Err(error) => {
+            error!({ action = "game-error", title = title, error = ?error }, "Error searching games");
+            say_game_error(ctx, &error).await?;
+            return Err(error.into());
+        }
+    };
+
+    let custom_id = format!("game-select-{}", ctx.id());
+    let options = results
+        .iter()
+        .take(MAX_GAME_OPTIONS)
+        .map(|result| CreateSelectMenuOption::new(format_option_label(result), &result.url))
+        .collect();
+    let menu = CreateSelectMenu::new(&custom_id, CreateSelectMenuKind::String { options })
+        .placeholder("Choose a game");
+
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .content("More than one game matches that title")
+                .components(vec![CreateActionRow::SelectMenu(menu)]),
+        )
+        .await?;
+
+    let interaction = ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(Duration::from_secs(60))
+        .filter(move |interaction| interaction.data.custom_id == custom_id)
+        .await;
+
+    let Some(interaction) = interaction else {
+        reply
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .content("No game was chosen")
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let game_url = match &interaction.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values.first().cloned(),
+        _ => None,
+    };
+
+    // Acknowledge the choice straight away, scraping the game page can take a few seconds
+    interaction
+        .create_response(ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let game_response = match game_url {
+        Some(game_url) => game_handler.handle_game_choice(&game_url).await,
+        None => Err(GameError::NotFound),
+    };
+
+    match game_response {
+        Ok(game) => {
+            info!({ action = "game-success", title = title, game = game.url }, "Successfully found game");
+            reply
+                .edit(
+                    ctx,
+                    CreateReply::default()
+                        .content("")
+                        .embed(embed::build_game_embed(&game))
+                        .components(vec![]),
+                )
+                .await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "game-error", title = title, error = ?error }, "Error fetching chosen game");
+            reply
+                .edit(ctx, CreateReply::default().components(vec![]))
+                .await?;
+            say_game_error(ctx, &error).await?;
+            return Err(error.into());
+        }
+    }
+}
+
+async fn say_game_error(
+    ctx: commands::Context<'_>,
+    error: &GameError,
+) -> Result<(), commands::Error> {
+    match error {
+        GameError::NoResults => {
+            let _ = ctx.say("No games on Backloggd match that title").await?;
+        }
+        GameError::NotFound => {
+            let _ = ctx
+                .say("That game's page couldn't be loaded. Please try again later")
+                .await?;
+        }
+        GameError::InternalError(..) => {
+            let _ = ctx
+                .say("The bot experienced an unexpected error. Please try again later")
+                .await?;
+        }
+    };
+
+    Ok(())
+}
+
+fn format_option_label(result: &GameSearchResult) -> String {
+    let label = match &result.year {
+        Some(year) => format!("{} ({})", result.title, year),
+        None => result.title.clone(),
+    };
+
+    // Discord rejects select menu labels longer than 100 characters
+    label.chars().take(100).collect()
+}
+
+pub struct GameHandler<S: Scraper> {
+    scraper: S,
+}
+
+impl<T: Scraper> GameHandler<T> {
+    fn new(scraper: T) -> Self {
+        return Self { scraper };
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_game(&self, title: &str) -> Result<GameSearch, GameError> {
+        info!("handling game command");
+
+        let results = self.scraper.search_games(title).await?;
+
+        if results.is_empty() {
+            return Err(GameError::NoResults);
+        }
+
+        match scraper::select_exact_match(&results, title) {
+            Some(result) => Ok(GameSearch::Found(Box::new(
+                self.handle_game_choice(&result.url).await?,
+            ))),
+            None => Ok(GameSearch::Ambiguous(results)),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_game_choice(&self, game_url: &str) -> Result<GameDetails, GameError> {
+        self.scraper
+            .get_game_details(game_url)
+            .await
+            .ok_or(GameError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_result(title: &str, year: Option<&str>) -> GameSearchResult {
+        GameSearchResult {
+            title: title.to_string(),
+            year: year.map(str::to_string),
+            url: format!("https://backloggd.com/games/{}/", title.to_lowercase()),
+        }
+    }
+
+    #[test]
+    fn format_option_label_includes_year() {
+        assert_eq!(
+            format_option_label(&build_result("Doom", Some("1993"))),
+            "Doom (1993)"
+        );
+        assert_eq!(format_option_label(&build_result("Doom", None)), "Doom");
+    }
+
+    #[test]
+    fn format_option_label_truncates_long_titles() {
+        let actual = format_option_label(&build_result(&"a".repeat(150), Some("2000")));
+
+        assert_eq!(actual.chars().count(), 100);
+    }
+}
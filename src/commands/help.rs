@@ -2,9 +2,9 @@ use tracing::instrument;
 
 use crate::commands;
 
-#[instrument(skip(ctx))]
+#[instrument(skip(_ctx))]
 #[poise::command(slash_command, prefix_command)]
-pub async fn help(ctx: commands::Context<'_>) -> Result<(), commands::Error> {
+pub async fn help(_ctx: commands::Context<'_>) -> Result<(), commands::Error> {
     Ok(())
 }
 
@@ -0,0 +1,143 @@
+use crate::commands;
+use crate::core::converter;
+use crate::core::models::StoredReview;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::stats;
+use crate::core::stats::StatsPeriod;
+use anyhow::Result;
+use poise::CreateReply;
+use std::collections::HashSet;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum Period {
+    #[name = "This month"]
+    Month,
+    #[name = "This year"]
+    Year,
+    #[name = "All time"]
+    AllTime,
+}
+
+impl From<Period> for StatsPeriod {
+    fn from(period: Period) -> Self {
+        match period {
+            Period::Month => StatsPeriod::Month,
+            Period::Year => StatsPeriod::Year,
+            Period::AllTime => StatsPeriod::AllTime,
+        }
+    }
+}
+
+/// Rank this server's Backloggd users by their reviews
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn leaderboard(
+    ctx: commands::Context<'_>,
+    #[description = "Reviews to rank, defaults to this month"] period: Option<Period>,
+) -> Result<(), commands::Error> {
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id,
+        None => {
+            let _ = ctx
+                .say("The leaderboard is only available in a server")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let period = period.map(Into::into).unwrap_or(StatsPeriod::Month);
+
+    // Looking up the server's channels can take longer than Discord waits for a response
+    ctx.defer().await?;
+
+    let repo = SqliteRepository {};
+
+    let channel_ids: Vec<u64> = guild_id
+        .channels(ctx)
+        .await?
+        .into_keys()
+        .map(|channel_id| channel_id.get())
+        .collect();
+
+    let linked_usernames: Vec<String> = repo
+        .get_guild_linked_accounts(&guild_id.get())
+        .await?
+        .into_iter()
+        .map(|account| account.username)
+        .collect();
+
+    let leaderboard_handler = LeaderboardHandler::new(repo);
+    let leaderboard_response = leaderboard_handler
+        .handle_leaderboard(&channel_ids, &linked_usernames, &period)
+        .await;
+
+    match leaderboard_response {
+        Ok(reviews) => {
+            info!({ action = "leaderboard-success", guild_id = guild_id.get(), reviews = reviews.len() }, "Successfully built leaderboard");
+            ctx.send(
+                CreateReply::default().embed(stats::build_leaderboard_embed(&period, &reviews)),
+            )
+            .await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "leaderboard-error", guild_id = guild_id.get(), error = ?error }, "Error building leaderboard");
+            let _ = ctx
+                .say("The bot experienced an unexpected error. Please try again later")
+                .await?;
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct LeaderboardHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> LeaderboardHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    /// Returns the reviews in the period from every feed subscribed in the given channels or
+    /// belonging to the given linked users.
+    #[instrument(skip(self))]
+    async fn handle_leaderboard(
+        &self,
+        channel_ids: &[u64],
+        linked_usernames: &[String],
+        period: &StatsPeriod,
+    ) -> Result<Vec<StoredReview>> {
+        info!("handling leaderboard command");
+
+        // Ranks users subscribed in any of the server's channels, and members who linked their
+        // account
+        let mut feed_ids: HashSet<i64> = self
+            .repository
+            .get_channel_feed_ids(channel_ids)
+            .await?
+            .into_iter()
+            .collect();
+
+        for username in linked_usernames {
+            let feed_url = commands::feed_url_for_username(username);
+
+            // Linked users' feeds are saved when they verify, but may have been removed since
+            if let Some(feed_id) = self.repository.get_feed_id(&feed_url).await? {
+                feed_ids.insert(feed_id);
+            }
+        }
+
+        let since = converter::format_sqlite_date(&period.start(chrono::Utc::now().naive_utc()));
+
+        let mut reviews = vec![];
+        for feed_id in feed_ids {
+            reviews.extend(self.repository.get_feed_reviews(&feed_id, &since).await?);
+        }
+
+        Ok(reviews)
+    }
+}
@@ -0,0 +1,267 @@
+use crate::commands;
+use crate::core::link;
+use crate::core::models::LinkedAccount;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::scraper::Scraper;
+use crate::core::validator;
+use anyhow::Result;
+use poise::CreateReply;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, Error)]
+pub enum LinkError {
+    #[error("The given username is not valid")]
+    InvalidUsername,
+    #[error("No Backloggd user has the given username")]
+    UserDoesNotExist,
+    #[error("Unexpected internal error arose while linking account")]
+    InternalError(#[from] anyhow::Error),
+}
+
+pub enum LinkStatus {
+    Verified,
+    // The user has to add the code to their bio and run /link again
+    CodeIssued(String),
+    CodeNotInBio(String),
+}
+
+/// Link your Discord account to your Backloggd account
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn link(
+    ctx: commands::Context<'_>,
+    #[description = "Your Backloggd username"] username: String,
+) -> Result<(), commands::Error> {
+    let user_id = ctx.author().id.get();
+    let guild_id = ctx.guild_id().map(|guild_id| guild_id.get());
+
+    // Checking the bio means scraping the profile page
+    ctx.defer_ephemeral().await?;
+
+    let repo = SqliteRepository {};
+
+    let scraper = ctx.data().scraper.clone();
+
+    let link_handler = LinkHandler::new(repo, scraper);
+    let link_response = link_handler
+        .handle_link(&user_id, guild_id, &username)
+        .await;
+
+    let message = match link_response {
+        Ok(status) => {
+            info!({ action = "link-success", user_id = user_id, username = username }, "Successfully handled link");
+            match status {
+                LinkStatus::Verified => format!(
+                    "Your Discord account is linked to {}. You can remove the code from your bio",
+                    username.trim()
+                ),
+                LinkStatus::CodeIssued(code) => format!(
+                    "Add `{}` to your Backloggd bio, then run /link again to finish linking",
+                    code
+                ),
+                LinkStatus::CodeNotInBio(code) => format!(
+                    "`{}` isn't in your Backloggd bio yet. Add it, then run /link again",
+                    code
+                ),
+            }
+        }
+        Err(error) => {
+            error!({ action = "link-error", user_id = user_id, username = username, error = ?error }, "Error linking account");
+            let message = match error {
+                LinkError::InvalidUsername => "The username you provided is invalid",
+                LinkError::UserDoesNotExist => "No Backloggd user has that username",
+                LinkError::InternalError(..) => {
+                    "The bot experienced an unexpected error. Please try again later"
+                }
+            };
+            ctx.send(CreateReply::default().content(message).ephemeral(true))
+                .await?;
+            return Err(error.into());
+        }
+    };
+
+    ctx.send(CreateReply::default().content(message).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Unlink your Discord account from your Backloggd account
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn unlink(ctx: commands::Context<'_>) -> Result<(), commands::Error> {
+    let user_id = ctx.author().id.get();
+
+    let repo = SqliteRepository {};
+
+    match unlink_account(&repo, &user_id).await {
+        Ok(_) => {
+            info!({ action = "unlink-success", user_id = user_id }, "Successfully unlinked account");
+            ctx.send(
+                CreateReply::default()
+                    .content("Your Discord account is no longer linked to Backloggd")
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "unlink-error", user_id = user_id, error = ?error }, "Error unlinking account");
+            ctx.send(
+                CreateReply::default()
+                    .content("The bot experienced an unexpected error. Please try again later")
+                    .ephemeral(true),
+            )
+            .await?;
+            return Err(error.into());
+        }
+    }
+}
+
+/// Removes the link, and stops polling the linked user's feed when it was only polled for the link.
+async fn unlink_account<R: Repository>(repository: &R, user_id: &u64) -> Result<()> {
+    let Some(account) = repository.get_linked_account(user_id).await? else {
+        return Ok(());
+    };
+
+    repository.delete_linked_account(user_id).await?;
+
+    if !account.verified {
+        return Ok(());
+    }
+
+    let feed_url = commands::feed_url_for_username(&account.username);
+
+    if let Some(feed_id) = repository.get_feed_id(&feed_url).await? {
+        if repository.get_subs(feed_id).await?.is_none() {
+            repository.delete_feed(&feed_id).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Falls back to the caller's linked account when no username is given. When there's neither it
+/// tells the caller and returns None.
+pub async fn username_or_linked(
+    ctx: commands::Context<'_>,
+    username: Option<String>,
+) -> Result<Option<String>, commands::Error> {
+    if username.is_some() {
+        return Ok(username);
+    }
+
+    let repo = SqliteRepository {};
+    let linked_username = find_linked_username(&repo, &ctx.author().id.get()).await?;
+
+    if linked_username.is_none() {
+        let _ = ctx
+            .say("Provide a username or link your Backloggd account with /link")
+            .await?;
+    }
+
+    Ok(linked_username)
+}
+
+/// Returns the Backloggd username the Discord user has verified, if any.
+pub async fn find_linked_username<R: Repository>(
+    repository: &R,
+    user_id: &u64,
+) -> Result<Option<String>> {
+    let account = repository.get_linked_account(user_id).await?;
+
+    Ok(link::verified_username(account))
+}
+
+pub struct LinkHandler<R: Repository, S: Scraper> {
+    repository: R,
+    scraper: S,
+}
+
+impl<T: Repository, U: Scraper> LinkHandler<T, U> {
+    fn new(repository: T, scraper: U) -> Self {
+        return Self {
+            repository,
+            scraper,
+        };
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_link(
+        &self,
+        user_id: &u64,
+        guild_id: Option<u64>,
+        username: &str,
+    ) -> Result<LinkStatus, LinkError> {
+        info!("handling link command");
+
+        let username = username.trim();
+
+        if validator::validate_username(username).is_err() {
+            return Err(LinkError::InvalidUsername);
+        }
+
+        let existing = self.repository.get_linked_account(user_id).await?;
+
+        // Running it again with the same username checks the bio for the code
+        let pending = existing.filter(|account| account.username.eq_ignore_ascii_case(username));
+
+        if let Some(account) = &pending {
+            if account.verified {
+                self.save_guild(user_id, guild_id).await?;
+                return Ok(LinkStatus::Verified);
+            }
+        }
+
+        let profile = self
+            .scraper
+            .get_user_profile(username)
+            .await?
+            .ok_or(LinkError::UserDoesNotExist)?;
+
+        // A new username always gets a new code, so codes can't be reused across accounts
+        let Some(mut account) = pending else {
+            let code = link::generate_verification_code();
+
+            self.repository
+                .save_linked_account(&LinkedAccount {
+                    user_id: *user_id,
+                    username: username.to_string(),
+                    verification_code: code.clone(),
+                    verified: false,
+                })
+                .await?;
+
+            return Ok(LinkStatus::CodeIssued(code));
+        };
+
+        if !link::bio_contains_code(profile.bio.as_deref(), &account.verification_code) {
+            return Ok(LinkStatus::CodeNotInBio(account.verification_code));
+        }
+
+        account.verified = true;
+        self.repository.save_linked_account(&account).await?;
+
+        // Polling the linked user's feed keeps their reviews in the leaderboard and stats
+        let feed_url = commands::feed_url_for_username(&account.username);
+        self.repository.save_feed(&feed_url).await?;
+
+        self.save_guild(user_id, guild_id).await?;
+
+        Ok(LinkStatus::Verified)
+    }
+
+    /// Linked users only show up on the leaderboards of servers they ran /link in.
+    async fn save_guild(&self, user_id: &u64, guild_id: Option<u64>) -> Result<()> {
+        if let Some(guild_id) = guild_id {
+            self.repository
+                .save_linked_account_guild(user_id, &guild_id)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
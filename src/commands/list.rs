@@ -28,10 +28,8 @@ fn build_sub_list_embed(subs: Vec<String>) -> CreateEmbed {
         description.push_str(item.as_str());
     }
 
-    let embed = poise::serenity_prelude::CreateEmbed::new()
+    poise::serenity_prelude::CreateEmbed::new()
         .color(Color::from_rgb(252, 99, 153))
         .title("Subscriptions")
-        .description(description);
-
-    embed
+        .description(description)
 }
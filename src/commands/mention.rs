@@ -0,0 +1,161 @@
+use crate::commands;
+use crate::core::models::Mention;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use anyhow::Result;
+use poise::serenity_prelude as serenity;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+use super::*;
+
+#[derive(Debug, Error)]
+pub enum MentionError {
+    #[error("Only one of role, user or here can be mentioned")]
+    ConflictingMentions,
+    #[error(transparent)]
+    Subscription(#[from] SubscriptionError),
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    default_member_permissions = "MANAGE_CHANNELS | MENTION_EVERYONE"
+)]
+pub async fn mention(
+    ctx: commands::Context<'_>,
+    #[description = "Backloggd RSS feed URL the channel is subscribed to"] feed_url: Option<String>,
+    #[description = "Username of the subscribed Backloggd user"] username: Option<String>,
+    #[description = "Role to ping when a new review is posted"] role: Option<serenity::Role>,
+    #[description = "User to ping when a new review is posted"] user: Option<serenity::User>,
+    #[description = "Ping @here when a new review is posted"] here: Option<bool>,
+) -> Result<(), commands::Error> {
+    let channel_id = ctx.channel_id().get();
+
+    let sub_request = SubRequest {
+        feed_url,
+        username,
+        channel_id: &channel_id,
+    };
+
+    let repo = SqliteRepository {};
+    let mention_handler = MentionHandler::new(repo);
+    let mention_response = match select_mention(
+        role.map(|role| role.id.get()),
+        user.map(|user| user.id.get()),
+        here.unwrap_or(false),
+    ) {
+        Ok(mention) => mention_handler.handle_mention(&sub_request, mention).await,
+        Err(error) => Err(error),
+    };
+
+    match mention_response {
+        Ok(None) => {
+            info!({ action = "mention-success", sub_request = ?sub_request }, "Successfully cleared subscription mention");
+            let _ = ctx
+                .say("New reviews from this feed will no longer ping anyone")
+                .await?;
+            return Ok(());
+        }
+        Ok(Some(_)) => {
+            info!({ action = "mention-success", sub_request = ?sub_request }, "Successfully set subscription mention");
+            let _ = ctx
+                .say("New reviews from this feed will ping the chosen mention")
+                .await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "mention-error", sub_request = ?sub_request, error = ?error }, "Error setting subscription mention");
+            match &error {
+                MentionError::ConflictingMentions => {
+                    let _ = ctx
+                        .say("You can only choose one of role, user or here")
+                        .await?;
+                }
+                MentionError::Subscription(error) => {
+                    say_subscription_error(ctx, error).await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+fn select_mention(
+    role_id: Option<u64>,
+    user_id: Option<u64>,
+    here: bool,
+) -> Result<Option<Mention>, MentionError> {
+    match (role_id, user_id, here) {
+        (None, None, false) => Ok(None),
+        (Some(id), None, false) => Ok(Some(Mention::Role(id))),
+        (None, Some(id), false) => Ok(Some(Mention::User(id))),
+        (None, None, true) => Ok(Some(Mention::Here)),
+        _ => Err(MentionError::ConflictingMentions),
+    }
+}
+
+pub struct MentionHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> MentionHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_mention(
+        &self,
+        request: &SubRequest<'_>,
+        mention: Option<Mention>,
+    ) -> Result<Option<Mention>, MentionError> {
+        info!("handling mention command");
+
+        let (_, sub) = find_subscription(&self.repository, request).await?;
+
+        self.repository
+            .update_sub_mention(&sub.rss_feed_id, request.channel_id, mention.as_ref())
+            .await
+            .map_err(SubscriptionError::from)?;
+
+        Ok(mention)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_mention_returns_none_when_no_options() {
+        let actual = select_mention(None, None, false);
+
+        assert!(matches!(actual, Ok(None)));
+    }
+
+    #[test]
+    fn select_mention_returns_chosen_mention() {
+        assert!(matches!(
+            select_mention(Some(1), None, false),
+            Ok(Some(Mention::Role(1)))
+        ));
+        assert!(matches!(
+            select_mention(None, Some(2), false),
+            Ok(Some(Mention::User(2)))
+        ));
+        assert!(matches!(
+            select_mention(None, None, true),
+            Ok(Some(Mention::Here))
+        ));
+    }
+
+    #[test]
+    fn select_mention_returns_error_when_options_conflict() {
+        let actual = select_mention(Some(1), Some(2), false);
+
+        assert!(matches!(actual, Err(MentionError::ConflictingMentions)));
+    }
+}
@@ -1,14 +1,49 @@
 pub mod about;
+pub mod backfill;
+pub mod compare;
+pub mod digest;
+pub mod game;
+pub mod mention;
 pub mod list;
 pub mod help;
+pub mod leaderboard;
+pub mod link;
+pub mod notify;
+pub mod preview;
+pub mod random;
+pub mod recap;
+pub mod recommend;
+pub mod review;
+pub mod search;
+pub mod settings;
+pub mod stats;
 pub mod sub;
+pub mod threads;
+pub mod unfurl;
 pub mod unsub;
+pub mod user;
+pub mod webhook;
 use thiserror::Error;
 
+use crate::core::cache::CachingScraper;
+use crate::core::cipher::SecretCipher;
+use crate::core::models::Subscription;
+use crate::core::repository::{Repository, SqliteRepository};
+use crate::core::scraper::{FeedStatus, ReqwestScraper};
 use crate::core::validator;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
 
 #[derive(Debug)]
-pub struct Data {}
+pub struct Data {
+    // Commands that post reviews need it to deliver through channel webhooks
+    pub cipher: Option<SecretCipher>,
+    // Shared so every command reuses the same connection pool, throttle and cache
+    pub scraper: CachingScraper<ReqwestScraper, SqliteRepository>,
+    // When each channel last got a link preview, keyed by channel id
+    pub unfurl_cooldowns: Mutex<HashMap<u64, Instant>>,
+}
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
@@ -27,15 +62,109 @@ pub enum SubError {
     InvalidUsername,
     #[error("The given feed does not exist")]
     FeedDoesNotExist,
+    #[error("Backloggd is rate limiting requests")]
+    RateLimited,
+    #[error("Backloggd could not be reached")]
+    BackloggdUnavailable,
     #[error("Must provide either a valid feed URL or username")]
     NoValidArguments,
     #[error("Unexpected internal error arose while deleting subscription")]
     InternalError(#[from] anyhow::Error),
 }
 
+/// Errors from commands that work with one of the channel's existing subscriptions.
+#[derive(Debug, Error)]
+pub enum SubscriptionError {
+    #[error("The given RSS feed URL is not valid")]
+    InvalidFeedUrl,
+    #[error("The given username is not valid")]
+    InvalidUsername,
+    #[error("Must provide either a valid feed URL or username")]
+    NoValidArguments,
+    #[error("The channel is not subscribed to the given feed")]
+    NotSubscribed,
+    #[error("Unexpected internal error arose while updating subscription")]
+    InternalError(#[from] anyhow::Error),
+}
+
+impl From<SubError> for SubscriptionError {
+    fn from(error: SubError) -> Self {
+        match error {
+            SubError::InvalidFeedUrl => SubscriptionError::InvalidFeedUrl,
+            SubError::InvalidUsername => SubscriptionError::InvalidUsername,
+            SubError::NoValidArguments => SubscriptionError::NoValidArguments,
+            SubError::InternalError(error) => SubscriptionError::InternalError(error),
+            // Only checking a feed on Backloggd gives these, which finding a subscription doesn't do
+            SubError::FeedDoesNotExist | SubError::RateLimited | SubError::BackloggdUnavailable => {
+                SubscriptionError::InternalError(error.into())
+            }
+        }
+    }
+}
+
+/// Tells the user why a command on one of the channel's subscriptions failed.
+pub async fn say_subscription_error(
+    ctx: Context<'_>,
+    error: &SubscriptionError,
+) -> Result<(), Error> {
+    let message = match error {
+        SubscriptionError::InvalidFeedUrl => "The feed_url you provided is invalid",
+        SubscriptionError::InvalidUsername => "The username you provided is invalid",
+        SubscriptionError::NoValidArguments => "You must provide a valid feed URL or username",
+        SubscriptionError::NotSubscribed => "This channel isn't subscribed to that feed",
+        SubscriptionError::InternalError(..) => {
+            "The bot experienced an unexpected error. Please try again later"
+        }
+    };
+
+    ctx.say(message).await?;
+
+    Ok(())
+}
+
+/// Finds the channel's subscription to the requested feed, with the feed's URL.
+pub async fn find_subscription<R: Repository>(
+    repository: &R,
+    request: &SubRequest<'_>,
+) -> Result<(String, Subscription), SubscriptionError> {
+    let feed_url = extract_feed_url(request)?;
+
+    // An unknown feed can't have any subscriptions
+    let Some(feed_id) = repository.get_feed_id(&feed_url).await? else {
+        return Err(SubscriptionError::NotSubscribed);
+    };
+
+    let sub = repository
+        .get_sub(&feed_id, request.channel_id)
+        .await?
+        .ok_or(SubscriptionError::NotSubscribed)?;
+
+    Ok((feed_url, sub))
+}
+
+/// Returns the fetched feed's content, or why it couldn't be fetched.
+pub fn check_feed_status(feed: Result<String, FeedStatus>) -> Result<String, SubError> {
+    feed.map_err(|status| match status {
+        FeedStatus::NotFound => SubError::FeedDoesNotExist,
+        FeedStatus::RateLimited => SubError::RateLimited,
+        FeedStatus::Unavailable => SubError::BackloggdUnavailable,
+    })
+}
+
+/// Tells the user why a feed couldn't be fetched from Backloggd.
+pub fn feed_status_message(status: FeedStatus) -> &'static str {
+    match status {
+        FeedStatus::NotFound => "Feed cannot be found for that user",
+        FeedStatus::RateLimited => {
+            "Backloggd is limiting how often the bot can make requests. Please try again in a few minutes"
+        }
+        FeedStatus::Unavailable => "Backloggd can't be reached right now. Please try again later",
+    }
+}
+
 pub fn extract_feed_url(request: &SubRequest) -> Result<String, SubError> {
     if let Some(feed_url) = &request.feed_url {
-        if validator::validate_feed_url(&feed_url).is_ok() {
+        if validator::validate_feed_url(feed_url).is_ok() {
             return Ok(feed_url.clone());
         } else {
             return Err(SubError::InvalidFeedUrl);
@@ -43,8 +172,8 @@ pub fn extract_feed_url(request: &SubRequest) -> Result<String, SubError> {
     }
 
     if let Some(username) = &request.username {
-        if validator::validate_username(&username).is_ok() {
-            return Ok(format!("https://backloggd.com/u/{username}/reviews/rss/").to_string());
+        if validator::validate_username(username).is_ok() {
+            return Ok(feed_url_for_username(username));
         } else {
             return Err(SubError::InvalidUsername);
         }
@@ -53,6 +182,10 @@ pub fn extract_feed_url(request: &SubRequest) -> Result<String, SubError> {
     Err(SubError::NoValidArguments)
 }
 
+pub fn feed_url_for_username(username: &str) -> String {
+    format!("https://backloggd.com/u/{username}/reviews/rss/")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,4 +255,36 @@ mod tests {
         let actual = extract_feed_url(&unsub_request);
         assert!(matches!(actual, Err(SubError::NoValidArguments)));
     }
+
+    #[test]
+    fn check_feed_status_only_reports_missing_feeds_as_missing() {
+        assert_eq!(
+            check_feed_status(Ok("<rss></rss>".to_string())).unwrap(),
+            "<rss></rss>"
+        );
+        assert!(matches!(
+            check_feed_status(Err(FeedStatus::NotFound)),
+            Err(SubError::FeedDoesNotExist)
+        ));
+        assert!(matches!(
+            check_feed_status(Err(FeedStatus::RateLimited)),
+            Err(SubError::RateLimited)
+        ));
+        assert!(matches!(
+            check_feed_status(Err(FeedStatus::Unavailable)),
+            Err(SubError::BackloggdUnavailable)
+        ));
+    }
+
+    #[test]
+    fn subscription_error_from_sub_error_keeps_argument_errors() {
+        assert!(matches!(
+            SubscriptionError::from(SubError::InvalidUsername),
+            SubscriptionError::InvalidUsername
+        ));
+        assert!(matches!(
+            SubscriptionError::from(SubError::RateLimited),
+            SubscriptionError::InternalError(..)
+        ));
+    }
 }
@@ -0,0 +1,95 @@
+use crate::commands;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use anyhow::Result;
+use tracing::instrument;
+use tracing::{error, info};
+
+use super::*;
+
+/// Toggles whether the caller is pinged when the channel posts a new review from the feed.
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command, rename = "notify-me")]
+pub async fn notify_me(
+    ctx: commands::Context<'_>,
+    #[description = "Backloggd RSS feed URL the channel is subscribed to"] feed_url: Option<String>,
+    #[description = "Username of the subscribed Backloggd user"] username: Option<String>,
+) -> Result<(), commands::Error> {
+    let channel_id = ctx.channel_id().get();
+    let user_id = ctx.author().id.get();
+
+    let sub_request = SubRequest {
+        feed_url,
+        username,
+        channel_id: &channel_id,
+    };
+
+    let repo = SqliteRepository {};
+    let notify_handler = NotifyHandler::new(repo);
+    let notify_response = notify_handler
+        .handle_notify_me(&sub_request, &user_id)
+        .await;
+
+    match notify_response {
+        Ok(true) => {
+            info!({ action = "notify-me-success", sub_request = ?sub_request }, "Successfully added user to mention list");
+            let _ = ctx
+                .say("You will be pinged when this feed posts a new review")
+                .await?;
+            return Ok(());
+        }
+        Ok(false) => {
+            info!({ action = "notify-me-success", sub_request = ?sub_request }, "Successfully removed user from mention list");
+            let _ = ctx
+                .say("You will no longer be pinged when this feed posts a new review")
+                .await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "notify-me-error", sub_request = ?sub_request, error = ?error }, "Error toggling user mention");
+            say_subscription_error(ctx, &error).await?;
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct NotifyHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> NotifyHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    /// Returns true if the user was added to the mention list, false if they were removed.
+    #[instrument(skip(self))]
+    async fn handle_notify_me(
+        &self,
+        request: &SubRequest<'_>,
+        user_id: &u64,
+    ) -> Result<bool, SubscriptionError> {
+        info!("handling notify-me command");
+
+        let (_, sub) = find_subscription(&self.repository, request).await?;
+        let feed_id = sub.rss_feed_id;
+
+        let notify_users = self
+            .repository
+            .get_notify_users(&feed_id, request.channel_id)
+            .await?;
+
+        if notify_users.contains(user_id) {
+            self.repository
+                .delete_notify_user(&feed_id, request.channel_id, user_id)
+                .await?;
+            return Ok(false);
+        }
+
+        self.repository
+            .save_notify_user(&feed_id, request.channel_id, user_id)
+            .await?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,125 @@
+use crate::commands;
+use crate::core::embed;
+use crate::core::parser;
+use crate::core::scraper;
+use crate::core::scraper::Scraper;
+use anyhow::Result;
+use poise::serenity_prelude::CreateEmbed;
+use poise::CreateReply;
+use tracing::instrument;
+use tracing::{error, info};
+
+use super::*;
+
+/// Show how a user's latest review will look when posted, without subscribing
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn preview(
+    ctx: commands::Context<'_>,
+    #[description = "Backloggd RSS feed URL to preview"] feed_url: Option<String>,
+    #[description = "Username of the Backloggd user to preview"] username: Option<String>,
+) -> Result<(), commands::Error> {
+    let channel_id = ctx.channel_id().get();
+
+    let sub_request = SubRequest {
+        feed_url,
+        username,
+        channel_id: &channel_id,
+    };
+
+    let scraper = ctx.data().scraper.clone();
+
+    let preview_handler = PreviewHandler::new(scraper);
+    let preview_response = preview_handler.handle_preview(&sub_request).await;
+
+    match preview_response {
+        Ok(Some(embed)) => {
+            info!({ action = "preview-success", sub_request = ?sub_request }, "Successfully previewed feed");
+            ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+                .await?;
+            return Ok(());
+        }
+        Ok(None) => {
+            info!({ action = "preview-success", sub_request = ?sub_request }, "Previewed feed has no reviews");
+            ctx.send(
+                CreateReply::default()
+                    .content("This user hasn't written any reviews yet")
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "preview-error", sub_request = ?sub_request, error = ?error }, "Error previewing feed");
+            let message = match error {
+                SubError::InvalidFeedUrl => "The feed_url you provided is invalid",
+                SubError::InvalidUsername => "The username you provided is invalid",
+                SubError::NoValidArguments => "You must provide a valid feed_url or username",
+                SubError::FeedDoesNotExist => feed_status_message(FeedStatus::NotFound),
+                SubError::RateLimited => feed_status_message(FeedStatus::RateLimited),
+                SubError::BackloggdUnavailable => feed_status_message(FeedStatus::Unavailable),
+                SubError::InternalError(..) => {
+                    "The bot experienced an unexpected error. Please try again later"
+                }
+            };
+            ctx.send(CreateReply::default().content(message).ephemeral(true))
+                .await?;
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct PreviewHandler<S: Scraper> {
+    scraper: S,
+}
+
+impl<T: Scraper> PreviewHandler<T> {
+    fn new(scraper: T) -> Self {
+        return Self { scraper };
+    }
+
+    /// Returns the embed for the feed's latest review, or None if the feed has no reviews.
+    #[instrument(skip(self))]
+    async fn handle_preview(
+        &self,
+        request: &SubRequest<'_>,
+    ) -> Result<Option<CreateEmbed>, SubError> {
+        info!("handling preview command");
+
+        let feed_url = extract_feed_url(request)?;
+
+        let content = check_feed_status(self.scraper.fetch_feed(&feed_url).await)?;
+
+        let rss_feed = parser::parse_rss_xml(&content)?;
+
+        let latest_item = match rss_feed
+            .channel
+            .item
+            .iter()
+            .max_by_key(|item| item.pub_date)
+        {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+
+        let profile_pic_url = match scraper::find_profile_url(&feed_url, &rss_feed.channel.item) {
+            Some(profile_url) => {
+                self.scraper
+                    .get_profile_pic_url_or_default(&profile_url)
+                    .await
+            }
+            None => None,
+        }
+        .unwrap_or(embed::DEFAULT_PROFILE_PIC_URL.to_string());
+
+        let review_metadata = self.scraper.get_review_metadata(&latest_item.link).await;
+        let footer = embed::build_footer(review_metadata);
+
+        Ok(Some(embed::build_review_embed(
+            &rss_feed.channel,
+            latest_item,
+            &profile_pic_url,
+            &footer,
+        )))
+    }
+}
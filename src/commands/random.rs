@@ -0,0 +1,114 @@
+use crate::commands;
+use crate::core::embed;
+use crate::core::models::ReviewQuery;
+use crate::core::recommend;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::scraper;
+use crate::core::scraper::Scraper;
+use anyhow::Result;
+use poise::serenity_prelude::CreateEmbed;
+use poise::CreateReply;
+use tracing::instrument;
+use tracing::{error, info};
+
+/// Show a random highly rated review posted in this server
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn random(ctx: commands::Context<'_>) -> Result<(), commands::Error> {
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id,
+        None => {
+            let _ = ctx
+                .say("Random reviews are only available in a server")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    // Scraping the reviewer's avatar can take longer than Discord waits for a response
+    ctx.defer().await?;
+
+    let channel_ids: Vec<u64> = guild_id
+        .channels(ctx)
+        .await?
+        .into_keys()
+        .map(|channel_id| channel_id.get())
+        .collect();
+
+    let repo = SqliteRepository {};
+
+    let scraper = ctx.data().scraper.clone();
+
+    let random_handler = RandomHandler::new(repo, scraper);
+    let random_response = random_handler.handle_random(&channel_ids).await;
+
+    match random_response {
+        Ok(Some(embed)) => {
+            info!({ action = "random-success", guild_id = guild_id.get() }, "Successfully picked random review");
+            ctx.send(CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        Ok(None) => {
+            info!({ action = "random-success", guild_id = guild_id.get() }, "No highly rated reviews to pick from");
+            let _ = ctx
+                .say("No highly rated reviews have been posted in this server yet")
+                .await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "random-error", guild_id = guild_id.get(), error = ?error }, "Error picking random review");
+            let _ = ctx
+                .say("The bot experienced an unexpected error. Please try again later")
+                .await?;
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct RandomHandler<R: Repository, S: Scraper> {
+    repository: R,
+    scraper: S,
+}
+
+impl<T: Repository, U: Scraper> RandomHandler<T, U> {
+    fn new(repository: T, scraper: U) -> Self {
+        return Self {
+            repository,
+            scraper,
+        };
+    }
+
+    /// Picks a highly rated review from every feed subscribed in the given channels, or None
+    /// when they have none.
+    #[instrument(skip(self))]
+    async fn handle_random(&self, channel_ids: &[u64]) -> Result<Option<CreateEmbed>> {
+        info!("handling random command");
+
+        // Channels subscribed to the same feed would otherwise make its reviews more likely
+        let feed_ids = self.repository.get_channel_feed_ids(channel_ids).await?;
+
+        let reviews = self
+            .repository
+            .find_reviews(&ReviewQuery {
+                feed_ids: Some(feed_ids),
+                ..Default::default()
+            })
+            .await?;
+
+        let Some(review) = recommend::pick_highly_rated(&reviews, rand::random()) else {
+            return Ok(None);
+        };
+
+        let profile_pic_url = self
+            .scraper
+            .get_profile_pic_url_or_default(&scraper::build_profile_url(&review.reviewer))
+            .await
+            .unwrap_or(embed::DEFAULT_PROFILE_PIC_URL.to_string());
+
+        Ok(Some(embed::build_stored_review_embed(
+            review,
+            &profile_pic_url,
+        )))
+    }
+}
@@ -0,0 +1,182 @@
+use crate::commands;
+use crate::core::converter;
+use crate::core::recap;
+use crate::core::recap::RecapPeriod;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use anyhow::Result;
+use poise::serenity_prelude::{CreateEmbed, Permissions};
+use poise::CreateReply;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum RecapMonth {
+    January,
+    February,
+    March,
+    April,
+    May,
+    June,
+    July,
+    August,
+    September,
+    October,
+    November,
+    December,
+}
+
+impl From<&RecapMonth> for u32 {
+    fn from(month: &RecapMonth) -> Self {
+        match month {
+            RecapMonth::January => 1,
+            RecapMonth::February => 2,
+            RecapMonth::March => 3,
+            RecapMonth::April => 4,
+            RecapMonth::May => 5,
+            RecapMonth::June => 6,
+            RecapMonth::July => 7,
+            RecapMonth::August => 8,
+            RecapMonth::September => 9,
+            RecapMonth::October => 10,
+            RecapMonth::November => 11,
+            RecapMonth::December => 12,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RecapError {
+    #[error("The given period hasn't ended yet")]
+    PeriodNotOver,
+    #[error("Unexpected internal error arose while building recap")]
+    InternalError(#[from] anyhow::Error),
+}
+
+/// Recap the channel's reviews for a past month or year
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn recap(
+    ctx: commands::Context<'_>,
+    #[description = "Year to recap, or the year of the month"]
+    #[min = 2000]
+    #[max = 2100]
+    year: Option<i32>,
+    #[description = "Month to recap"] month: Option<RecapMonth>,
+    #[description = "Post recaps here at the end of each month and year"] auto: Option<bool>,
+) -> Result<(), commands::Error> {
+    let channel_id = ctx.channel_id().get();
+
+    let recap_handler = RecapHandler::new(SqliteRepository {});
+
+    if let Some(enabled) = auto {
+        // Anyone can look at a recap, but only channel managers choose what gets posted here
+        let can_manage_channel = ctx
+            .author_member()
+            .await
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| permissions.contains(Permissions::MANAGE_CHANNELS));
+
+        if !can_manage_channel {
+            let _ = ctx
+                .say("Only members who can manage this channel can turn automatic recaps on or off")
+                .await?;
+            return Ok(());
+        }
+
+        match recap_handler.handle_auto_recap(&channel_id, enabled).await {
+            Ok(()) => {
+                info!({ action = "recap-auto-success", channel_id = channel_id, enabled = enabled }, "Successfully updated automatic recaps");
+                let message = if enabled {
+                    "A recap will be posted here at the end of each month and year"
+                } else {
+                    "Recaps will no longer be posted here automatically"
+                };
+                let _ = ctx.say(message).await?;
+            }
+            Err(error) => {
+                error!({ action = "recap-auto-error", channel_id = channel_id, error = ?error }, "Error updating automatic recaps");
+                let _ = ctx
+                    .say("The bot experienced an unexpected error. Please try again later")
+                    .await?;
+                return Err(error.into());
+            }
+        }
+
+        if year.is_none() && month.is_none() {
+            return Ok(());
+        }
+    }
+
+    let month = month.as_ref().map(u32::from);
+    let recap_response = recap_handler.handle_recap(&channel_id, year, month).await;
+
+    match recap_response {
+        Ok((period, embed)) => {
+            info!({ action = "recap-success", channel_id = channel_id, period = period.name() }, "Successfully built recap");
+            ctx.send(CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "recap-error", channel_id = channel_id, error = ?error }, "Error building recap");
+            match error {
+                RecapError::PeriodNotOver => {
+                    let _ = ctx
+                        .say("That period hasn't ended yet, recaps are only made for past periods")
+                        .await?;
+                }
+                RecapError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct RecapHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> RecapHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_auto_recap(&self, channel_id: &u64, enabled: bool) -> Result<()> {
+        info!("handling recap auto option");
+
+        if !enabled {
+            return self.repository.delete_recap_channel(channel_id).await;
+        }
+
+        // The first automatic recap is for the month that's in progress
+        let month = recap::last_completed_month(chrono::Utc::now().naive_utc());
+
+        self.repository
+            .save_recap_channel(channel_id, &converter::format_sqlite_date(&month.start()))
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_recap(
+        &self,
+        channel_id: &u64,
+        year: Option<i32>,
+        month: Option<u32>,
+    ) -> Result<(RecapPeriod, CreateEmbed), RecapError> {
+        info!("handling recap command");
+
+        // Without a year or month it recaps last month. Only a year recaps the whole year
+        let period = recap::resolve_period(year, month, chrono::Utc::now().naive_utc())
+            .ok_or(RecapError::PeriodNotOver)?;
+
+        let embed = recap::build_channel_recap(&self.repository, channel_id, &period).await?;
+
+        Ok((period, embed))
+    }
+}
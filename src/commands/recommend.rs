@@ -0,0 +1,159 @@
+use crate::commands;
+use crate::commands::link::find_linked_username;
+use crate::core::models::ReviewQuery;
+use crate::core::recommend;
+use crate::core::recommend::Recommendation;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::scraper;
+use crate::core::scraper::Scraper;
+use anyhow::Result;
+use poise::CreateReply;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+// Each candidate takes two requests to Backloggd, so only the best few are checked
+const MAX_CANDIDATES_CHECKED: usize = 10;
+
+#[derive(Debug, Error)]
+pub enum RecommendError {
+    #[error("The user hasn't linked a Backloggd account")]
+    NotLinked,
+    #[error("Unexpected internal error arose while recommending games")]
+    InternalError(#[from] anyhow::Error),
+}
+
+/// Get games to play based on this server's reviews
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn recommend(ctx: commands::Context<'_>) -> Result<(), commands::Error> {
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id,
+        None => {
+            let _ = ctx
+                .say("Recommendations are only available in a server")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let user_id = ctx.author().id.get();
+
+    // Checking the caller's logs takes several requests to Backloggd
+    ctx.defer().await?;
+
+    let channel_ids: Vec<u64> = guild_id
+        .channels(ctx)
+        .await?
+        .into_keys()
+        .map(|channel_id| channel_id.get())
+        .collect();
+
+    let repo = SqliteRepository {};
+
+    let scraper = ctx.data().scraper.clone();
+
+    let recommend_handler = RecommendHandler::new(repo, scraper);
+    let recommend_response = recommend_handler
+        .handle_recommend(&user_id, &channel_ids)
+        .await;
+
+    match recommend_response {
+        Ok((username, recommendations)) => {
+            info!({ action = "recommend-success", user_id = user_id, username = username, recommendations = recommendations.len() }, "Successfully recommended games");
+            let embed = recommend::build_recommend_embed(&username, &recommendations);
+            ctx.send(CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "recommend-error", user_id = user_id, error = ?error }, "Error recommending games");
+            match error {
+                RecommendError::NotLinked => {
+                    let _ = ctx
+                        .say("Link your Backloggd account with /link to get recommendations")
+                        .await?;
+                }
+                RecommendError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct RecommendHandler<R: Repository, S: Scraper> {
+    repository: R,
+    scraper: S,
+}
+
+impl<T: Repository, U: Scraper> RecommendHandler<T, U> {
+    fn new(repository: T, scraper: U) -> Self {
+        return Self {
+            repository,
+            scraper,
+        };
+    }
+
+    /// Returns the caller's linked username with the games recommended to them. Stored reviews
+    /// only rule out games the caller reviewed, so the best candidates are checked against their
+    /// Backloggd logs too.
+    #[instrument(skip(self))]
+    async fn handle_recommend(
+        &self,
+        user_id: &u64,
+        channel_ids: &[u64],
+    ) -> Result<(String, Vec<Recommendation>), RecommendError> {
+        info!("handling recommend command");
+
+        let username = find_linked_username(&self.repository, user_id)
+            .await?
+            .ok_or(RecommendError::NotLinked)?;
+
+        // Channels subscribed to the same feed would count each review more than once
+        let feed_ids = self.repository.get_channel_feed_ids(channel_ids).await?;
+
+        let reviews = self
+            .repository
+            .find_reviews(&ReviewQuery {
+                feed_ids: Some(feed_ids),
+                ..Default::default()
+            })
+            .await?;
+
+        let mut recommendations = vec![];
+
+        for candidate in recommend::recommend_games(&reviews, &username)
+            .into_iter()
+            .take(MAX_CANDIDATES_CHECKED)
+        {
+            if recommendations.len() == recommend::RECOMMENDATION_COUNT {
+                break;
+            }
+
+            let results = self.scraper.search_games(&candidate.title).await?;
+
+            // A game Backloggd can't find can't have been logged either
+            let Some(result) =
+                scraper::select_exact_match(&results, &candidate.title).or(results.first())
+            else {
+                recommendations.push(candidate);
+                continue;
+            };
+
+            if self
+                .scraper
+                .get_game_log(&username, &result.url)
+                .await?
+                .is_none()
+            {
+                recommendations.push(candidate);
+            }
+        }
+
+        Ok((username, recommendations))
+    }
+}
@@ -0,0 +1,182 @@
+use crate::commands;
+use crate::commands::link::username_or_linked;
+use crate::core::embed;
+use crate::core::parser;
+use crate::core::review;
+use crate::core::scraper;
+use crate::core::scraper::FeedStatus;
+use crate::core::scraper::Scraper;
+use crate::core::validator;
+use anyhow::Result;
+use poise::serenity_prelude::CreateEmbed;
+use poise::CreateReply;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, Error)]
+pub enum ReviewError {
+    #[error("The given username is not valid")]
+    InvalidUsername,
+    #[error("No Backloggd user has the given username")]
+    UserDoesNotExist,
+    #[error("No games matched the given title")]
+    GameNotFound,
+    #[error("The user hasn't logged the game")]
+    NotLogged,
+    #[error("Backloggd is rate limiting requests")]
+    RateLimited,
+    #[error("Backloggd could not be reached")]
+    BackloggdUnavailable,
+    #[error("Unexpected internal error arose while finding review")]
+    InternalError(#[from] anyhow::Error),
+}
+
+/// Show what a Backloggd user thought of a game
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn review(
+    ctx: commands::Context<'_>,
+    #[description = "Title of the game"] game: String,
+    #[description = "Username of the Backloggd user, defaults to your linked account"]
+    username: Option<String>,
+) -> Result<(), commands::Error> {
+    let Some(username) = username_or_linked(ctx, username).await? else {
+        return Ok(());
+    };
+
+    // Finding the review can take several requests to Backloggd
+    ctx.defer().await?;
+
+    let scraper = ctx.data().scraper.clone();
+
+    let review_handler = ReviewHandler::new(scraper);
+    let review_response = review_handler.handle_review(&username, &game).await;
+
+    match review_response {
+        Ok(embed) => {
+            info!({ action = "review-success", username = username, game = game }, "Successfully found review");
+            ctx.send(CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "review-error", username = username, game = game, error = ?error }, "Error finding review");
+            match error {
+                ReviewError::InvalidUsername => {
+                    let _ = ctx.say("The username you provided is invalid").await?;
+                }
+                ReviewError::UserDoesNotExist => {
+                    let _ = ctx.say("No Backloggd user has that username").await?;
+                }
+                ReviewError::GameNotFound => {
+                    let _ = ctx.say("No games on Backloggd match that title").await?;
+                }
+                ReviewError::NotLogged => {
+                    let _ = ctx
+                        .say(format!("{} hasn't logged that game", username.trim()))
+                        .await?;
+                }
+                ReviewError::RateLimited => {
+                    let _ = ctx
+                        .say(commands::feed_status_message(FeedStatus::RateLimited))
+                        .await?;
+                }
+                ReviewError::BackloggdUnavailable => {
+                    let _ = ctx
+                        .say(commands::feed_status_message(FeedStatus::Unavailable))
+                        .await?;
+                }
+                ReviewError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct ReviewHandler<S: Scraper> {
+    scraper: S,
+}
+
+impl<T: Scraper> ReviewHandler<T> {
+    fn new(scraper: T) -> Self {
+        return Self { scraper };
+    }
+
+    /// Looks for the review in the user's feed first, since that matches what the publisher
+    /// posts. Older reviews are found through the user's log of the game, which also gives a
+    /// rating or status to fall back on when there's no review.
+    #[instrument(skip(self))]
+    async fn handle_review(&self, username: &str, game: &str) -> Result<CreateEmbed, ReviewError> {
+        info!("handling review command");
+
+        let username = username.trim();
+
+        if validator::validate_username(username).is_err() {
+            return Err(ReviewError::InvalidUsername);
+        }
+
+        let feed_url = commands::feed_url_for_username(username);
+
+        let content = match self.scraper.fetch_feed(&feed_url).await {
+            Ok(content) => content,
+            Err(FeedStatus::NotFound) => {
+                return Err(ReviewError::UserDoesNotExist);
+            }
+            Err(FeedStatus::RateLimited) => return Err(ReviewError::RateLimited),
+            Err(FeedStatus::Unavailable) => return Err(ReviewError::BackloggdUnavailable),
+        };
+
+        let rss_feed = parser::parse_rss_xml(&content)?;
+
+        if let Some(item) = review::find_feed_review(&rss_feed.channel.item, game) {
+            let profile_pic_url = self
+                .scraper
+                .get_profile_pic_url_or_default(&scraper::build_profile_url(username))
+                .await
+                .unwrap_or(embed::DEFAULT_PROFILE_PIC_URL.to_string());
+
+            let review_metadata = self.scraper.get_review_metadata(&item.link).await;
+            let footer = embed::build_footer(review_metadata);
+
+            return Ok(embed::build_review_embed(
+                &rss_feed.channel,
+                item,
+                &profile_pic_url,
+                &footer,
+            ));
+        }
+
+        let results = self.scraper.search_games(game).await?;
+
+        // Without a select menu here, an ambiguous title goes to Backloggd's best match
+        let result = scraper::select_exact_match(&results, game)
+            .or(results.first())
+            .ok_or(ReviewError::GameNotFound)?;
+
+        let log = self
+            .scraper
+            .get_game_log(username, &result.url)
+            .await?
+            .ok_or(ReviewError::NotLogged)?;
+
+        if let Some(review_url) = &log.review_url {
+            if let Some(preview) = self.scraper.get_page_preview(review_url).await {
+                return Ok(embed::build_scraped_review_embed(
+                    username, &log, review_url, preview,
+                ));
+            }
+        }
+
+        let avatar_url = self
+            .scraper
+            .get_profile_pic_url_or_default(&scraper::build_profile_url(username))
+            .await
+            .unwrap_or(embed::DEFAULT_PROFILE_PIC_URL.to_string());
+
+        Ok(embed::build_game_log_embed(username, &avatar_url, &log))
+    }
+}
@@ -0,0 +1,267 @@
+use crate::commands;
+use crate::core::models::{ReviewSearch, ReviewSearchResult};
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::search;
+use crate::core::validator;
+use anyhow::Result;
+use chrono::Duration as ChronoDuration;
+use poise::serenity_prelude::{
+    ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use poise::CreateReply;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug)]
+pub struct SearchRequest {
+    query: String,
+    reviewer: Option<String>,
+    min_rating: Option<f32>,
+    max_rating: Option<f32>,
+    since: Option<String>,
+    until: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum SearchError {
+    #[error("The search has no words to look for")]
+    EmptyQuery,
+    #[error("The given reviewer is not a valid username")]
+    InvalidUsername,
+    #[error("The given date is not valid")]
+    InvalidDate,
+    #[error("Unexpected internal error arose while searching reviews")]
+    InternalError(#[from] anyhow::Error),
+}
+
+/// Search the reviews posted in this server
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn search(
+    ctx: commands::Context<'_>,
+    #[description = "Words to look for in review titles and text"] query: String,
+    #[description = "Username of the reviewer"] reviewer: Option<String>,
+    #[description = "Lowest rating in stars"]
+    #[min = 0.5]
+    #[max = 5.0]
+    min_rating: Option<f32>,
+    #[description = "Highest rating in stars"]
+    #[min = 0.5]
+    #[max = 5.0]
+    max_rating: Option<f32>,
+    #[description = "Earliest review date, e.g. 2025-01-31"] since: Option<String>,
+    #[description = "Latest review date, e.g. 2025-12-31"] until: Option<String>,
+) -> Result<(), commands::Error> {
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id,
+        None => {
+            let _ = ctx.say("Search is only available in a server").await?;
+            return Ok(());
+        }
+    };
+
+    let search_request = SearchRequest {
+        query,
+        reviewer,
+        min_rating,
+        max_rating,
+        since,
+        until,
+    };
+
+    // Looking up the server's channels and searching their feeds can take a while
+    ctx.defer().await?;
+
+    let channel_ids: Vec<u64> = guild_id
+        .channels(ctx)
+        .await?
+        .into_keys()
+        .map(|channel_id| channel_id.get())
+        .collect();
+
+    let repo = SqliteRepository {};
+    let search_handler = SearchHandler::new(repo);
+    let search_response = search_handler
+        .handle_search(&channel_ids, &search_request)
+        .await;
+
+    let results = match search_response {
+        Ok(results) => {
+            info!({ action = "search-success", search_request = ?search_request, results = results.len() }, "Successfully searched reviews");
+            results
+        }
+        Err(error) => {
+            error!({ action = "search-error", search_request = ?search_request, error = ?error }, "Error searching reviews");
+            match error {
+                SearchError::EmptyQuery => {
+                    let _ = ctx.say("Provide some words to search for").await?;
+                }
+                SearchError::InvalidUsername => {
+                    let _ = ctx.say("The reviewer you provided is invalid").await?;
+                }
+                SearchError::InvalidDate => {
+                    let _ = ctx
+                        .say("The date you provided is invalid, use YYYY-MM-DD like 2025-01-31")
+                        .await?;
+                }
+                SearchError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+
+    let page_count = search::page_count(&results);
+    let mut page = 0;
+
+    let embed = search::build_search_embed(&search_request.query, &results, page);
+
+    if page_count == 1 {
+        ctx.send(CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let previous_id = format!("search-previous-{}", ctx.id());
+    let next_id = format!("search-next-{}", ctx.id());
+
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .embed(embed)
+                .components(build_page_buttons(&previous_id, &next_id, page, page_count)),
+        )
+        .await?;
+
+    // Each button press restarts the timeout, so the buttons stay while someone is paging
+    while let Some(interaction) = ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(Duration::from_secs(120))
+        .filter({
+            let previous_id = previous_id.clone();
+            let next_id = next_id.clone();
+            move |interaction| {
+                interaction.data.custom_id == previous_id || interaction.data.custom_id == next_id
+            }
+        })
+        .await
+    {
+        if interaction.data.custom_id == next_id {
+            page = (page + 1).min(page_count - 1);
+        } else {
+            page = page.saturating_sub(1);
+        }
+
+        interaction
+            .create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(search::build_search_embed(
+                            &search_request.query,
+                            &results,
+                            page,
+                        ))
+                        .components(build_page_buttons(&previous_id, &next_id, page, page_count)),
+                ),
+            )
+            .await?;
+    }
+
+    reply
+        .edit(ctx, CreateReply::default().components(vec![]))
+        .await?;
+
+    Ok(())
+}
+
+fn build_page_buttons(
+    previous_id: &str,
+    next_id: &str,
+    page: usize,
+    page_count: usize,
+) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(previous_id)
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        CreateButton::new(next_id)
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= page_count),
+    ])]
+}
+
+pub struct SearchHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> SearchHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    /// Searches the reviews of every feed subscribed in the given channels.
+    #[instrument(skip(self))]
+    async fn handle_search(
+        &self,
+        channel_ids: &[u64],
+        request: &SearchRequest,
+    ) -> Result<Vec<ReviewSearchResult>, SearchError> {
+        info!("handling search command");
+
+        let fts_query = search::to_fts_query(&request.query).ok_or(SearchError::EmptyQuery)?;
+
+        let reviewer = match &request.reviewer {
+            Some(reviewer) if validator::validate_username(reviewer.trim()).is_err() => {
+                return Err(SearchError::InvalidUsername);
+            }
+            reviewer => reviewer
+                .as_ref()
+                .map(|reviewer| reviewer.trim().to_string()),
+        };
+
+        let since = match &request.since {
+            Some(since) => {
+                Some(search::parse_date_filter(since).map_err(|_| SearchError::InvalidDate)?)
+            }
+            None => None,
+        };
+
+        // The until date is inclusive, so search up to the start of the next day
+        let until = match &request.until {
+            Some(until) => Some(
+                search::parse_date_filter(until).map_err(|_| SearchError::InvalidDate)?
+                    + ChronoDuration::days(1),
+            ),
+            None => None,
+        };
+
+        let feed_ids = self.repository.get_channel_feed_ids(channel_ids).await?;
+
+        if feed_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let search = ReviewSearch {
+            fts_query,
+            feed_ids,
+            reviewer,
+            since,
+            until,
+            min_rating: request.min_rating.map(search::stars_to_rating),
+            max_rating: request.max_rating.map(search::stars_to_rating),
+            limit: search::MAX_SEARCH_RESULTS,
+        };
+
+        Ok(self.repository.search_reviews(&search).await?)
+    }
+}
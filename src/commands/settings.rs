@@ -0,0 +1,146 @@
+use crate::commands;
+use crate::core::models::{DeletedReviewAction, GuildSettings};
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use anyhow::Result;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum DeletedReviews {
+    #[name = "Delete the post"]
+    Delete,
+    #[name = "Mark the post as deleted"]
+    Mark,
+}
+
+impl From<DeletedReviews> for DeletedReviewAction {
+    fn from(deleted_reviews: DeletedReviews) -> Self {
+        match deleted_reviews {
+            DeletedReviews::Delete => DeletedReviewAction::Delete,
+            DeletedReviews::Mark => DeletedReviewAction::Mark,
+        }
+    }
+}
+
+/// Change how the bot behaves in this server
+#[instrument(skip(ctx))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn settings(
+    ctx: commands::Context<'_>,
+    #[description = "What to do with posts of reviews deleted on Backloggd"]
+    deleted_reviews: Option<DeletedReviews>,
+    #[description = "Preview Backloggd links posted in chat"] link_previews: Option<bool>,
+) -> Result<(), commands::Error> {
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id.get(),
+        None => {
+            let _ = ctx.say("Settings can only be changed in a server").await?;
+            return Ok(());
+        }
+    };
+
+    let repo = SqliteRepository {};
+    let settings_handler = SettingsHandler::new(repo);
+    let settings_response = settings_handler
+        .handle_settings(&guild_id, deleted_reviews.map(Into::into), link_previews)
+        .await;
+
+    match settings_response {
+        Ok(settings) => {
+            info!({ action = "settings-success", guild_id = guild_id }, "Successfully updated guild settings");
+            let _ = ctx.say(describe_settings(&settings)).await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "settings-error", guild_id = guild_id, error = ?error }, "Error updating guild settings");
+            let _ = ctx
+                .say("The bot experienced an unexpected error. Please try again later")
+                .await?;
+            return Err(error.into());
+        }
+    };
+}
+
+fn describe_settings(settings: &GuildSettings) -> String {
+    let deleted_reviews = match settings.deleted_review_action {
+        DeletedReviewAction::Delete => "Posts of reviews deleted on Backloggd will be deleted",
+        DeletedReviewAction::Mark => {
+            "Posts of reviews deleted on Backloggd will be marked as deleted"
+        }
+    };
+
+    let link_previews = if settings.unfurl_links {
+        "Backloggd links posted in chat will get a preview"
+    } else {
+        "Backloggd links posted in chat won't get a preview"
+    };
+
+    format!("{}\n{}", deleted_reviews, link_previews)
+}
+
+pub struct SettingsHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> SettingsHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    /// Applies the given changes and returns the guild's settings afterwards.
+    #[instrument(skip(self))]
+    async fn handle_settings(
+        &self,
+        guild_id: &u64,
+        deleted_review_action: Option<DeletedReviewAction>,
+        unfurl_links: Option<bool>,
+    ) -> Result<GuildSettings> {
+        info!("handling settings command");
+
+        let mut settings = self.repository.get_guild_settings(guild_id).await?;
+
+        // Running it without any options shows the current settings
+        if deleted_review_action.is_none() && unfurl_links.is_none() {
+            return Ok(settings);
+        }
+
+        if let Some(deleted_review_action) = deleted_review_action {
+            settings.deleted_review_action = deleted_review_action;
+        }
+
+        if let Some(unfurl_links) = unfurl_links {
+            settings.unfurl_links = unfurl_links;
+        }
+
+        self.repository.save_guild_settings(&settings).await?;
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_settings_returns_each_setting() {
+        let settings = GuildSettings {
+            guild_id: 1,
+            deleted_review_action: DeletedReviewAction::Delete,
+            unfurl_links: true,
+        };
+
+        let actual = describe_settings(&settings);
+
+        assert_eq!(
+            actual,
+            "Posts of reviews deleted on Backloggd will be deleted\nBackloggd links posted in chat will get a preview"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use crate::commands;
+use crate::commands::link::username_or_linked;
+use crate::core::models::{ReviewQuery, StoredReview};
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::stats;
+use crate::core::validator;
+use anyhow::Result;
+use poise::CreateReply;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, Error)]
+pub enum StatsError {
+    #[error("The given username is not valid")]
+    InvalidUsername,
+    #[error("Unexpected internal error arose while building stats")]
+    InternalError(#[from] anyhow::Error),
+}
+
+/// Show a Backloggd user's review stats
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn stats(
+    ctx: commands::Context<'_>,
+    #[description = "Username of the Backloggd user, defaults to your linked account"]
+    username: Option<String>,
+) -> Result<(), commands::Error> {
+    let Some(username) = username_or_linked(ctx, username).await? else {
+        return Ok(());
+    };
+
+    let repo = SqliteRepository {};
+
+    let stats_handler = StatsHandler::new(repo);
+    let stats_response = stats_handler.handle_stats(&username).await;
+
+    match stats_response {
+        Ok(reviews) => {
+            info!({ action = "stats-success", username = username, reviews = reviews.len() }, "Successfully built stats");
+            let embed =
+                stats::build_stats_embed(username.trim(), &reviews, chrono::Utc::now().naive_utc());
+            ctx.send(CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "stats-error", username = username, error = ?error }, "Error building stats");
+            match error {
+                StatsError::InvalidUsername => {
+                    let _ = ctx.say("The username you provided is invalid").await?;
+                }
+                StatsError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct StatsHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> StatsHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    /// Returns every review kept for the user, or none when their feed isn't being polled.
+    #[instrument(skip(self))]
+    async fn handle_stats(&self, username: &str) -> Result<Vec<StoredReview>, StatsError> {
+        info!("handling stats command");
+
+        let username = username.trim();
+
+        if validator::validate_username(username).is_err() {
+            return Err(StatsError::InvalidUsername);
+        }
+
+        let feed_url = commands::feed_url_for_username(username);
+
+        // Stats only cover reviews seen while the user's feed was being polled
+        let Some(feed_id) = self.repository.get_feed_id(&feed_url).await? else {
+            return Ok(vec![]);
+        };
+
+        Ok(self
+            .repository
+            .find_reviews(&ReviewQuery {
+                feed_ids: Some(vec![feed_id]),
+                ..Default::default()
+            })
+            .await?)
+    }
+}
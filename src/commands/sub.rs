@@ -1,10 +1,12 @@
 use crate::commands;
+use crate::commands::backfill::run_backfill;
+use crate::commands::link::find_linked_username;
 use crate::core::repository::Repository;
 use crate::core::repository::SqliteRepository;
-use crate::core::scraper::ReqwestScraper;
 use crate::core::scraper::Scraper;
 use anyhow::Result;
-use reqwest::Client;
+use poise::serenity_prelude as serenity;
+use poise::CreateReply;
 use tracing::instrument;
 use tracing::{error, info};
 
@@ -18,9 +20,59 @@ pub async fn sub(
     feed_url: Option<String>,
     #[description = "Username of the Backloggd user you want to unsubscribe the channel from"]
     username: Option<String>,
+    #[description = "Number of recent reviews to post right away"]
+    #[min = 1]
+    #[max = 20]
+    backfill: Option<u8>,
+) -> Result<(), commands::Error> {
+    // Without a feed or username, subscribe to the caller's linked account
+    let username = match (&feed_url, username) {
+        (None, None) => {
+            let repo = SqliteRepository {};
+            find_linked_username(&repo, &ctx.author().id.get()).await?
+        }
+        (_, username) => username,
+    };
+
+    run_sub(ctx, feed_url, username, backfill).await
+}
+
+/// Subscribe the channel to this user's linked Backloggd account
+#[instrument(skip(ctx))]
+#[poise::command(context_menu_command = "Subscribe to Backloggd reviews")]
+pub async fn sub_user(
+    ctx: commands::Context<'_>,
+    user: serenity::User,
+) -> Result<(), commands::Error> {
+    let repo = SqliteRepository {};
+
+    match find_linked_username(&repo, &user.id.get()).await? {
+        Some(username) => run_sub(ctx, None, Some(username), None).await,
+        None => {
+            ctx.send(
+                CreateReply::default()
+                    .content(format!("{} hasn't linked a Backloggd account", user.name))
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+    }
+}
+
+async fn run_sub(
+    ctx: commands::Context<'_>,
+    feed_url: Option<String>,
+    username: Option<String>,
+    backfill: Option<u8>,
 ) -> Result<(), commands::Error> {
     let channel_id = ctx.channel_id().get();
 
+    // Posting recent reviews takes longer than Discord waits for a response
+    if backfill.is_some() {
+        ctx.defer().await?;
+    }
+
     let sub_request = SubRequest {
         feed_url,
         username,
@@ -29,8 +81,7 @@ pub async fn sub(
 
     let repo = SqliteRepository {};
 
-    let client = Client::new();
-    let scraper = ReqwestScraper::new(client);
+    let scraper = ctx.data().scraper.clone();
 
     let sub_handler = SubHandler::new(repo, scraper);
     let sub_response = sub_handler.handle_sub(&sub_request).await;
@@ -39,6 +90,21 @@ pub async fn sub(
         Ok(_) => {
             info!({ action = "sub-success", sub_request = ?sub_request }, "Successfully unsubscribed user from feed");
             let _ = ctx.say("Successfully subscribed to feed").await?;
+
+            if let Some(count) = backfill {
+                match run_backfill(ctx, &sub_request, count).await {
+                    Ok(posted) => {
+                        info!({ action = "backfill-success", sub_request = ?sub_request, posted = posted }, "Successfully backfilled reviews");
+                    }
+                    Err(error) => {
+                        error!({ action = "backfill-error", sub_request = ?sub_request, error = ?error }, "Error backfilling reviews");
+                        let _ = ctx
+                            .say("Unable to post recent reviews. Try /backfill later")
+                            .await?;
+                    }
+                }
+            }
+
             return Ok(());
         }
         Err(error) => {
@@ -52,11 +118,21 @@ pub async fn sub(
                 }
                 SubError::NoValidArguments => {
                     let _ = ctx
-                        .say("You must provide a valid feed_url or username")
+                        .say("You must provide a valid feed_url or username, or link your Backloggd account with /link")
                         .await?;
                 }
                 SubError::FeedDoesNotExist => {
-                    let _ = ctx.say("Feed cannot be found for that user").await?;
+                    let _ = ctx.say(feed_status_message(FeedStatus::NotFound)).await?;
+                }
+                SubError::RateLimited => {
+                    let _ = ctx
+                        .say(feed_status_message(FeedStatus::RateLimited))
+                        .await?;
+                }
+                SubError::BackloggdUnavailable => {
+                    let _ = ctx
+                        .say(feed_status_message(FeedStatus::Unavailable))
+                        .await?;
                 }
                 SubError::InternalError(..) => {
                     let _ = ctx
@@ -86,17 +162,9 @@ impl<T: Repository, U: Scraper> SubHandler<T, U> {
     async fn handle_sub(&self, sub_request: &SubRequest<'_>) -> Result<(), SubError> {
         info!("handling sub command");
 
-        let feed_url = extract_feed_url(&sub_request)?;
+        let feed_url = extract_feed_url(sub_request)?;
 
-        let user_exists = self
-            .scraper
-            .does_feed_exist(&feed_url)
-            .await
-            .map_err(|err| SubError::InternalError(err))?;
-
-        if !user_exists {
-            return Err(SubError::FeedDoesNotExist);
-        }
+        check_feed_status(self.scraper.fetch_feed(&feed_url).await)?;
 
         // TODO: trim URL before inserting. Want to decrease risk of same URL with non-meaningful
         // characters creating duplicate entries
@@ -104,13 +172,12 @@ impl<T: Repository, U: Scraper> SubHandler<T, U> {
             .repository
             .save_feed(&feed_url)
             .await
-            .map_err(|err| SubError::InternalError(err))?;
+            .map_err(SubError::InternalError)?;
 
-        let _ = self
-            .repository
+        self.repository
             .save_sub(&id, sub_request.channel_id)
             .await
-            .map_err(|err| SubError::InternalError(err))?;
+            .map_err(SubError::InternalError)?;
 
         return Ok(());
     }
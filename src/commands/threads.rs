@@ -0,0 +1,121 @@
+use crate::commands;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use anyhow::Result;
+use tracing::instrument;
+use tracing::{error, info};
+
+use super::*;
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum ThreadArchive {
+    #[name = "1 hour"]
+    OneHour,
+    #[name = "24 hours"]
+    OneDay,
+    #[name = "3 days"]
+    ThreeDays,
+    #[name = "1 week"]
+    OneWeek,
+}
+
+impl ThreadArchive {
+    fn minutes(&self) -> u16 {
+        match self {
+            ThreadArchive::OneHour => 60,
+            ThreadArchive::OneDay => 1440,
+            ThreadArchive::ThreeDays => 4320,
+            ThreadArchive::OneWeek => 10080,
+        }
+    }
+}
+
+/// Start a discussion thread on each review posted from a feed
+#[instrument(skip(ctx))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    default_member_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn threads(
+    ctx: commands::Context<'_>,
+    #[description = "Start a thread on each new review"] enabled: bool,
+    #[description = "Backloggd RSS feed URL the channel is subscribed to"] feed_url: Option<String>,
+    #[description = "Username of the subscribed Backloggd user"] username: Option<String>,
+    #[description = "Archive idle threads after"] archive: Option<ThreadArchive>,
+) -> Result<(), commands::Error> {
+    let channel_id = ctx.channel_id().get();
+
+    let sub_request = SubRequest {
+        feed_url,
+        username,
+        channel_id: &channel_id,
+    };
+
+    let repo = SqliteRepository {};
+    let threads_handler = ThreadsHandler::new(repo);
+    let threads_response = threads_handler
+        .handle_threads(
+            &sub_request,
+            enabled,
+            archive.as_ref().map(ThreadArchive::minutes),
+        )
+        .await;
+
+    match threads_response {
+        Ok(_) => {
+            info!({ action = "threads-success", sub_request = ?sub_request }, "Successfully updated subscription threads");
+            if enabled {
+                let _ = ctx
+                    .say("New reviews from this feed will start a discussion thread")
+                    .await?;
+            } else {
+                let _ = ctx
+                    .say("New reviews from this feed will no longer start a thread")
+                    .await?;
+            }
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "threads-error", sub_request = ?sub_request, error = ?error }, "Error updating subscription threads");
+            say_subscription_error(ctx, &error).await?;
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct ThreadsHandler<R: Repository> {
+    repository: R,
+}
+
+impl<T: Repository> ThreadsHandler<T> {
+    fn new(repository: T) -> Self {
+        return Self { repository };
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_threads(
+        &self,
+        request: &SubRequest<'_>,
+        enabled: bool,
+        archive_minutes: Option<u16>,
+    ) -> Result<(), SubscriptionError> {
+        info!("handling threads command");
+
+        let (_, sub) = find_subscription(&self.repository, request).await?;
+
+        // In forum channels every review is already its own post, so this only sets the archive
+        // duration there. Posts are tagged using forum tags named ★ to ★★★★★ or Unrated when
+        // they exist
+        self.repository
+            .update_sub_threads(
+                &sub.rss_feed_id,
+                request.channel_id,
+                enabled,
+                archive_minutes,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
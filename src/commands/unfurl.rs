@@ -0,0 +1,132 @@
+use crate::commands;
+use crate::core::embed;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use crate::core::scraper::Scraper;
+use crate::core::unfurl;
+use anyhow::Result;
+use poise::serenity_prelude::{
+    self as serenity, CreateAllowedMentions, CreateEmbed, CreateMessage, FullEvent,
+};
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+use tracing::instrument;
+use tracing::{error, info};
+
+/// Replies to messages containing Backloggd links with a preview, in guilds that opted in.
+pub async fn event_handler(
+    ctx: &serenity::Context,
+    event: &FullEvent,
+    _framework: poise::FrameworkContext<'_, commands::Data, commands::Error>,
+    data: &commands::Data,
+) -> Result<(), commands::Error> {
+    let FullEvent::Message { new_message } = event else {
+        return Ok(());
+    };
+
+    // Never unfurl the bot's own review posts, or other bots and webhooks
+    if new_message.author.bot || new_message.webhook_id.is_some() {
+        return Ok(());
+    }
+
+    let Some(guild_id) = new_message.guild_id else {
+        return Ok(());
+    };
+
+    let channel_id = new_message.channel_id.get();
+
+    let unfurl_handler = UnfurlHandler::new(SqliteRepository {}, data.scraper.clone());
+    let unfurl_response = unfurl_handler
+        .handle_unfurl(
+            &guild_id.get(),
+            &channel_id,
+            &new_message.content,
+            &data.unfurl_cooldowns,
+        )
+        .await;
+
+    match unfurl_response {
+        Ok(embeds) if embeds.is_empty() => {
+            return Ok(());
+        }
+        Ok(embeds) => {
+            info!({ action = "unfurl-success", channel_id = channel_id, links = embeds.len() }, "Successfully unfurled Backloggd links");
+            let message = CreateMessage::new()
+                .embeds(embeds)
+                .reference_message(new_message)
+                .allowed_mentions(CreateAllowedMentions::new());
+
+            new_message.channel_id.send_message(ctx, message).await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "unfurl-error", channel_id = channel_id, error = ?error }, "Error unfurling Backloggd links");
+            return Err(error.into());
+        }
+    }
+}
+
+pub struct UnfurlHandler<R: Repository, S: Scraper> {
+    repository: R,
+    scraper: S,
+}
+
+impl<T: Repository, U: Scraper> UnfurlHandler<T, U> {
+    fn new(repository: T, scraper: U) -> Self {
+        return Self {
+            repository,
+            scraper,
+        };
+    }
+
+    /// Returns an embed for each Backloggd link that could be scraped, or none when the guild
+    /// hasn't enabled link previews or the channel is on cooldown.
+    #[instrument(skip(self, content, cooldowns))]
+    async fn handle_unfurl(
+        &self,
+        guild_id: &u64,
+        channel_id: &u64,
+        content: &str,
+        cooldowns: &Mutex<HashMap<u64, Instant>>,
+    ) -> Result<Vec<CreateEmbed>> {
+        // Most messages have no links, so check that before touching the database
+        let links = unfurl::find_backloggd_links(content);
+
+        if links.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let settings = self.repository.get_guild_settings(guild_id).await?;
+
+        if !settings.unfurl_links {
+            return Ok(vec![]);
+        }
+
+        {
+            let now = Instant::now();
+            // A panic while holding the lock can't leave the cooldowns half updated, so keep using them
+            let mut cooldowns = cooldowns.lock().unwrap_or_else(PoisonError::into_inner);
+
+            if unfurl::is_on_cooldown(cooldowns.get(channel_id).copied(), now) {
+                info!("Channel {} is on unfurl cooldown", channel_id);
+                return Ok(vec![]);
+            }
+
+            cooldowns.insert(*channel_id, now);
+        }
+
+        info!("handling unfurl of {} links", links.len());
+
+        let mut embeds = vec![];
+
+        for link in &links {
+            match self.scraper.get_page_preview(link.url()).await {
+                Some(preview) => embeds.push(embed::build_link_embed(link, preview)),
+                None => info!("Unable to scrape preview for {}", link.url()),
+            }
+        }
+
+        Ok(embeds)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::commands;
 use crate::core::repository::Repository;
 use crate::core::repository::SqliteRepository;
+use anyhow::anyhow;
 use anyhow::Result;
 use tracing::error;
 use tracing::info;
@@ -49,7 +50,10 @@ pub async fn unsub(
                         .say("You must provide a valid feed URL or username")
                         .await?;
                 }
-                SubError::InternalError(..) | SubError::FeedDoesNotExist => {
+                SubError::InternalError(..)
+                | SubError::FeedDoesNotExist
+                | SubError::RateLimited
+                | SubError::BackloggdUnavailable => {
                     let _ = ctx
                         .say("The bot experienced an unexpected error. Please try again later")
                         .await?;
@@ -76,12 +80,18 @@ impl<T: Repository> UnsubHandler<T> {
         let feed_url = extract_feed_url(request)?;
 
         // TODO: remove feed if this is the last sub to the feed?
-        let feed_id = self.repository.get_feed_id(&feed_url).await?;
+        let feed_id = self
+            .repository
+            .get_feed_id(&feed_url)
+            .await?
+            .ok_or(anyhow!(
+                "No RssFeeds entry with the given URL exists in the database"
+            ))?;
         let _ = self
             .repository
             .delete_sub(&feed_id, request.channel_id)
             .await
-            .map_err(|err| SubError::InternalError(err));
+            .map_err(SubError::InternalError);
 
         Ok(())
     }
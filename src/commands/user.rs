@@ -0,0 +1,126 @@
+use crate::commands;
+use crate::commands::link::{find_linked_username, username_or_linked};
+use crate::core::embed;
+use crate::core::repository::SqliteRepository;
+use crate::core::scraper::Scraper;
+use crate::core::scraper::UserProfile;
+use crate::core::validator;
+use anyhow::Result;
+use poise::serenity_prelude as serenity;
+use poise::CreateReply;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, Error)]
+pub enum UserError {
+    #[error("The given username is not valid")]
+    InvalidUsername,
+    #[error("No Backloggd user has the given username")]
+    UserDoesNotExist,
+    #[error("Unexpected internal error arose while fetching profile")]
+    InternalError(#[from] anyhow::Error),
+}
+
+/// Show a Backloggd user's profile card
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command)]
+pub async fn user(
+    ctx: commands::Context<'_>,
+    #[description = "Username of the Backloggd user, defaults to your linked account"]
+    username: Option<String>,
+) -> Result<(), commands::Error> {
+    let Some(username) = username_or_linked(ctx, username).await? else {
+        return Ok(());
+    };
+
+    send_user_profile(ctx, &username).await
+}
+
+/// Show the Backloggd profile card of this user's linked account
+#[instrument(skip(ctx))]
+#[poise::command(context_menu_command = "Backloggd profile")]
+pub async fn user_profile(
+    ctx: commands::Context<'_>,
+    user: serenity::User,
+) -> Result<(), commands::Error> {
+    let repo = SqliteRepository {};
+
+    match find_linked_username(&repo, &user.id.get()).await? {
+        Some(username) => send_user_profile(ctx, &username).await,
+        None => {
+            ctx.send(
+                CreateReply::default()
+                    .content(format!("{} hasn't linked a Backloggd account", user.name))
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+    }
+}
+
+async fn send_user_profile(
+    ctx: commands::Context<'_>,
+    username: &str,
+) -> Result<(), commands::Error> {
+    // Scraping the profile can take longer than Discord waits for a response
+    ctx.defer().await?;
+
+    let scraper = ctx.data().scraper.clone();
+
+    let user_handler = UserHandler::new(scraper);
+    let user_response = user_handler.handle_user(username).await;
+
+    match user_response {
+        Ok(profile) => {
+            info!({ action = "user-success", username = username }, "Successfully fetched user profile");
+            ctx.send(CreateReply::default().embed(embed::build_user_embed(&profile)))
+                .await?;
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "user-error", username = username, error = ?error }, "Error fetching user profile");
+            match error {
+                UserError::InvalidUsername => {
+                    let _ = ctx.say("The username you provided is invalid").await?;
+                }
+                UserError::UserDoesNotExist => {
+                    let _ = ctx.say("No Backloggd user has that username").await?;
+                }
+                UserError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct UserHandler<S: Scraper> {
+    scraper: S,
+}
+
+impl<T: Scraper> UserHandler<T> {
+    fn new(scraper: T) -> Self {
+        return Self { scraper };
+    }
+
+    #[instrument(skip(self))]
+    async fn handle_user(&self, username: &str) -> Result<UserProfile, UserError> {
+        info!("handling user command");
+
+        let username = username.trim();
+
+        if validator::validate_username(username).is_err() {
+            return Err(UserError::InvalidUsername);
+        }
+
+        self.scraper
+            .get_user_profile(username)
+            .await?
+            .ok_or(UserError::UserDoesNotExist)
+    }
+}
@@ -0,0 +1,125 @@
+use crate::commands;
+use crate::core::repository::Repository;
+use crate::core::repository::SqliteRepository;
+use poise::serenity_prelude::WebhookId;
+use thiserror::Error;
+use tracing::instrument;
+use tracing::{error, info};
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("No webhook_key is configured to encrypt webhook tokens with")]
+    NotConfigured,
+    #[error("Unexpected internal error arose while saving webhook settings")]
+    InternalError(#[from] anyhow::Error),
+}
+
+/// Switches the channel between posting reviews as the bot and as the reviewer via a webhook.
+#[instrument(skip(ctx))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    default_member_permissions = "MANAGE_WEBHOOKS"
+)]
+pub async fn webhook(
+    ctx: commands::Context<'_>,
+    #[description = "Post reviews using the reviewer's name and avatar"] enabled: bool,
+) -> Result<(), commands::Error> {
+    let channel_id = ctx.channel_id().get();
+
+    let repo = SqliteRepository {};
+    let webhook_handler = WebhookHandler::new(repo, ctx.data().cipher.is_some());
+    let webhook_response = webhook_handler.handle_webhook(&channel_id, enabled).await;
+
+    match webhook_response {
+        Ok(removed_webhook_id) => {
+            info!({ action = "webhook-success", channel_id = channel_id, enabled = enabled }, "Successfully updated channel webhook delivery");
+
+            // The bot created the webhook so it's allowed to clean it up
+            if let Some(webhook_id) = removed_webhook_id {
+                if let Err(error) = ctx
+                    .http()
+                    .delete_webhook(
+                        WebhookId::new(webhook_id),
+                        Some("Webhook delivery disabled"),
+                    )
+                    .await
+                {
+                    error!({ action = "webhook-error", channel_id = channel_id, error = ?error }, "Error deleting channel webhook");
+                }
+            }
+
+            if enabled {
+                let _ = ctx
+                    .say("Reviews in this channel will be posted with the reviewer's name and avatar")
+                    .await?;
+            } else {
+                let _ = ctx
+                    .say("Reviews in this channel will be posted by the bot")
+                    .await?;
+            }
+            return Ok(());
+        }
+        Err(error) => {
+            error!({ action = "webhook-error", channel_id = channel_id, error = ?error }, "Error updating channel webhook delivery");
+            match error {
+                WebhookError::NotConfigured => {
+                    let _ = ctx
+                        .say("Webhook delivery isn't set up for this bot, reviews will keep being posted by the bot")
+                        .await?;
+                }
+                WebhookError::InternalError(..) => {
+                    let _ = ctx
+                        .say("The bot experienced an unexpected error. Please try again later")
+                        .await?;
+                }
+            };
+            return Err(error.into());
+        }
+    };
+}
+
+pub struct WebhookHandler<R: Repository> {
+    repository: R,
+    // Without a webhook_key the publisher can't store webhook tokens, so it never uses webhooks
+    configured: bool,
+}
+
+impl<T: Repository> WebhookHandler<T> {
+    fn new(repository: T, configured: bool) -> Self {
+        return Self {
+            repository,
+            configured,
+        };
+    }
+
+    /// Returns the id of the webhook that should be deleted from Discord, if any.
+    #[instrument(skip(self))]
+    async fn handle_webhook(
+        &self,
+        channel_id: &u64,
+        enabled: bool,
+    ) -> Result<Option<u64>, WebhookError> {
+        info!("handling webhook command");
+
+        if enabled {
+            if !self.configured {
+                return Err(WebhookError::NotConfigured);
+            }
+
+            // The publisher creates the webhook itself when it posts the first review
+            self.repository.save_webhook_channel(channel_id).await?;
+            return Ok(None);
+        }
+
+        let webhook_id = self
+            .repository
+            .get_webhook_channel(channel_id)
+            .await?
+            .and_then(|webhook| webhook.webhook_id);
+
+        self.repository.delete_webhook_channel(channel_id).await?;
+
+        Ok(webhook_id)
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+use super::parser::RssItem;
+
+// Each backfilled review is its own message, so keep a single command from flooding the channel
+pub const MAX_BACKFILL_COUNT: u8 = 20;
+
+/// Returns the `count` most recent items that haven't been posted to the channel yet, oldest
+/// first so the channel reads in the order the reviews were written.
+pub fn select_backfill_items<'a>(
+    items: &'a [RssItem],
+    delivered_guids: &HashSet<String>,
+    count: usize,
+) -> Vec<&'a RssItem> {
+    let mut items: Vec<&RssItem> = items
+        .iter()
+        .filter(|item| !delivered_guids.contains(&item.guid))
+        .collect();
+
+    items.sort_by_key(|item| std::cmp::Reverse(item.pub_date));
+    items.truncate(count);
+    items.reverse();
+
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::build_test_item;
+
+    fn guids(items: &[&RssItem]) -> Vec<String> {
+        items.iter().map(|item| item.guid.clone()).collect()
+    }
+
+    #[test]
+    fn select_backfill_items_returns_most_recent_oldest_first() {
+        let items = vec![
+            build_test_item("review-3", 3),
+            build_test_item("review-1", 1),
+            build_test_item("review-4", 4),
+            build_test_item("review-2", 2),
+        ];
+
+        let actual = select_backfill_items(&items, &HashSet::new(), 3);
+
+        assert_eq!(guids(&actual), vec!["review-2", "review-3", "review-4"]);
+    }
+
+    #[test]
+    fn select_backfill_items_skips_delivered_reviews() {
+        let items = vec![
+            build_test_item("review-1", 1),
+            build_test_item("review-2", 2),
+            build_test_item("review-3", 3),
+        ];
+        let delivered_guids = HashSet::from(["review-3".to_string()]);
+
+        let actual = select_backfill_items(&items, &delivered_guids, 2);
+
+        assert_eq!(guids(&actual), vec!["review-1", "review-2"]);
+    }
+
+    #[test]
+    fn select_backfill_items_returns_all_when_count_exceeds_feed() {
+        let items = vec![build_test_item("review-1", 1)];
+
+        let actual = select_backfill_items(&items, &HashSet::new(), 5);
+
+        assert_eq!(guids(&actual), vec!["review-1"]);
+    }
+}
@@ -0,0 +1,381 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{instrument, warn};
+
+use super::converter;
+use super::repository::Repository;
+use super::scraper::{
+    FeedStatus, GameDetails, GameLog, GameSearchResult, PagePreview, ReviewMetadata, RssRequest,
+    RssResponse, Scraper, UserProfile,
+};
+use anyhow::Error;
+
+const PROFILE_PIC_PREFIX: &str = "profile_pic:";
+const REVIEW_METADATA_PREFIX: &str = "review_metadata:";
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    // Avatars rarely change, so they're kept for a long time
+    pub profile_pic_ttl: Duration,
+    // Likes and comments keep changing, and the refresh loop invalidates them before scraping
+    pub review_metadata_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            profile_pic_ttl: Duration::hours(24),
+            review_metadata_ttl: Duration::hours(1),
+        }
+    }
+}
+
+/// In-memory values that expire, keyed by what was scraped.
+#[derive(Debug, Default)]
+pub struct TtlCache {
+    entries: Mutex<HashMap<String, (String, NaiveDateTime)>>,
+}
+
+impl TtlCache {
+    pub fn get(&self, key: &str, now: NaiveDateTime) -> Option<String> {
+        let mut entries = self.entries.lock().ok()?;
+
+        match entries.get(key) {
+            Some((value, expires_at)) if *expires_at > now => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: &str, value: String, expires_at: NaiveDateTime) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key.to_string(), (value, expires_at));
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheStats {
+    /// Counts a lookup and returns the hits and misses so far.
+    pub fn record(&self, hit: bool) -> (u64, u64) {
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Wraps a scraper to cache profile pictures and review metadata, in memory and in the repository
+/// when one is given so they survive restarts. Everything else goes straight to the inner scraper.
+#[derive(Debug, Clone)]
+pub struct CachingScraper<S: Scraper, R: Repository> {
+    inner: S,
+    repository: Option<R>,
+    config: CacheConfig,
+    // Shared by clones, like the inner scraper's connection pool
+    memory: Arc<TtlCache>,
+    stats: Arc<CacheStats>,
+}
+
+impl<S: Scraper, R: Repository> CachingScraper<S, R> {
+    pub fn new(inner: S, repository: Option<R>, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            repository,
+            config,
+            memory: Arc::new(TtlCache::default()),
+            stats: Arc::new(CacheStats::default()),
+        }
+    }
+
+    #[instrument(skip(self), fields(cache, hits, misses))]
+    async fn get_cached(&self, key: &str) -> Option<String> {
+        let now = Utc::now().naive_utc();
+
+        let value = match self.memory.get(key, now) {
+            Some(value) => Some(value),
+            None => self.get_persisted(key, now).await,
+        };
+
+        let (hits, misses) = self.stats.record(value.is_some());
+
+        let span = tracing::Span::current();
+        span.record("cache", if value.is_some() { "hit" } else { "miss" });
+        span.record("hits", hits);
+        span.record("misses", misses);
+
+        value
+    }
+
+    async fn get_persisted(&self, key: &str, now: NaiveDateTime) -> Option<String> {
+        let repository = self.repository.as_ref()?;
+
+        match repository
+            .get_cache_entry(key, &converter::format_sqlite_date(&now))
+            .await
+        {
+            Ok(Some(entry)) => {
+                self.memory
+                    .insert(key, entry.value.clone(), entry.expires_at);
+                Some(entry.value)
+            }
+            Ok(None) => None,
+            Err(error) => {
+                warn!({ key = key, error = ?error }, "Error reading cache entry");
+                None
+            }
+        }
+    }
+
+    async fn set_cached(&self, key: &str, value: &str, ttl: Duration) {
+        let expires_at = Utc::now().naive_utc() + ttl;
+
+        self.memory.insert(key, value.to_string(), expires_at);
+
+        if let Some(repository) = &self.repository {
+            if let Err(error) = repository
+                .save_cache_entry(key, value, &converter::format_sqlite_date(&expires_at))
+                .await
+            {
+                warn!({ key = key, error = ?error }, "Error saving cache entry");
+            }
+        }
+    }
+
+    async fn set_cached_review_metadata(&self, key: &str, metadata: &ReviewMetadata) {
+        if let Ok(value) = serde_json::to_string(metadata) {
+            self.set_cached(key, &value, self.config.review_metadata_ttl)
+                .await;
+        }
+    }
+}
+
+impl<S: Scraper, R: Repository> Scraper for CachingScraper<S, R> {
+    async fn get_rss_feed_content(&self, request: &RssRequest) -> Result<RssResponse, Error> {
+        self.inner.get_rss_feed_content(request).await
+    }
+
+    async fn get_profile_pic_url_or_default(&self, profile_url: &str) -> Option<String> {
+        let key = format!("{}{}", PROFILE_PIC_PREFIX, profile_url);
+
+        if let Some(url) = self.get_cached(&key).await {
+            return Some(url);
+        }
+
+        // Failed scrapes aren't cached, so the next call tries again
+        let url = self
+            .inner
+            .get_profile_pic_url_or_default(profile_url)
+            .await?;
+        self.set_cached(&key, &url, self.config.profile_pic_ttl)
+            .await;
+
+        Some(url)
+    }
+
+    async fn get_review_metadata(&self, review_url: &str) -> Option<ReviewMetadata> {
+        let key = format!("{}{}", REVIEW_METADATA_PREFIX, review_url);
+
+        if let Some(metadata) = self
+            .get_cached(&key)
+            .await
+            .and_then(|value| serde_json::from_str(&value).ok())
+        {
+            return Some(metadata);
+        }
+
+        let metadata = self.inner.get_review_metadata(review_url).await?;
+        self.set_cached_review_metadata(&key, &metadata).await;
+
+        Some(metadata)
+    }
+
+    async fn refresh_review_metadata(&self, review_url: &str) -> Option<ReviewMetadata> {
+        let key = format!("{}{}", REVIEW_METADATA_PREFIX, review_url);
+
+        // A failed scrape keeps the cached metadata, which is still the last known value
+        let metadata = self.inner.refresh_review_metadata(review_url).await?;
+        self.set_cached_review_metadata(&key, &metadata).await;
+
+        Some(metadata)
+    }
+
+    async fn fetch_feed(&self, feed_url: &str) -> Result<String, FeedStatus> {
+        self.inner.fetch_feed(feed_url).await
+    }
+
+    async fn is_review_deleted(&self, review_url: &str) -> bool {
+        self.inner.is_review_deleted(review_url).await
+    }
+
+    async fn get_page_preview(&self, page_url: &str) -> Option<PagePreview> {
+        self.inner.get_page_preview(page_url).await
+    }
+
+    async fn search_games(&self, query: &str) -> Result<Vec<GameSearchResult>, Error> {
+        self.inner.search_games(query).await
+    }
+
+    async fn get_game_details(&self, game_url: &str) -> Option<GameDetails> {
+        self.inner.get_game_details(game_url).await
+    }
+
+    async fn get_user_profile(&self, username: &str) -> Result<Option<UserProfile>, Error> {
+        self.inner.get_user_profile(username).await
+    }
+
+    async fn get_game_log(&self, username: &str, game_url: &str) -> Result<Option<GameLog>, Error> {
+        self.inner.get_game_log(username, game_url).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::repository::SqliteRepository;
+    use anyhow::anyhow;
+    use chrono::NaiveDate;
+
+    fn build_date(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, 1)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    /// Counts how often profile pictures and review metadata are scraped, everything else fails.
+    #[derive(Default)]
+    struct CountingScraper {
+        calls: AtomicU64,
+    }
+
+    impl Scraper for CountingScraper {
+        async fn get_rss_feed_content(&self, _request: &RssRequest) -> Result<RssResponse, Error> {
+            Err(anyhow!("not scraped in tests"))
+        }
+
+        async fn get_profile_pic_url_or_default(&self, _profile_url: &str) -> Option<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            None
+        }
+
+        async fn get_review_metadata(&self, _review_url: &str) -> Option<ReviewMetadata> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+
+            Some(ReviewMetadata {
+                likes: Some(calls.to_string()),
+                comments: None,
+                status: Some("Completed".to_string()),
+            })
+        }
+
+        async fn fetch_feed(&self, _feed_url: &str) -> Result<String, FeedStatus> {
+            Err(FeedStatus::Unavailable)
+        }
+
+        async fn is_review_deleted(&self, _review_url: &str) -> bool {
+            false
+        }
+
+        async fn get_page_preview(&self, _page_url: &str) -> Option<PagePreview> {
+            None
+        }
+
+        async fn search_games(&self, _query: &str) -> Result<Vec<GameSearchResult>, Error> {
+            Err(anyhow!("not scraped in tests"))
+        }
+
+        async fn get_game_details(&self, _game_url: &str) -> Option<GameDetails> {
+            None
+        }
+
+        async fn get_user_profile(&self, _username: &str) -> Result<Option<UserProfile>, Error> {
+            Err(anyhow!("not scraped in tests"))
+        }
+
+        async fn get_game_log(
+            &self,
+            _username: &str,
+            _game_url: &str,
+        ) -> Result<Option<GameLog>, Error> {
+            Err(anyhow!("not scraped in tests"))
+        }
+    }
+
+    fn build_scraper() -> CachingScraper<CountingScraper, SqliteRepository> {
+        CachingScraper::new(CountingScraper::default(), None, CacheConfig::default())
+    }
+
+    #[test]
+    fn ttl_cache_expires_entries() {
+        let cache = TtlCache::default();
+
+        cache.insert("key", "value".to_string(), build_date(12));
+
+        assert_eq!(cache.get("key", build_date(11)).as_deref(), Some("value"));
+        assert_eq!(cache.get("key", build_date(12)), None);
+        assert_eq!(cache.get("key", build_date(11)), None);
+    }
+
+    #[test]
+    fn cache_stats_counts_hits_and_misses() {
+        let stats = CacheStats::default();
+
+        stats.record(false);
+        stats.record(true);
+
+        assert_eq!(stats.record(true), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn caching_scraper_reuses_review_metadata_until_refreshed() {
+        let scraper = build_scraper();
+        let review_url = "https://backloggd.com/u/username1/review/1/";
+
+        let first = scraper.get_review_metadata(review_url).await;
+        let second = scraper.get_review_metadata(review_url).await;
+
+        assert_eq!(first, second);
+        assert_eq!(scraper.inner.calls.load(Ordering::SeqCst), 1);
+
+        let third = scraper.refresh_review_metadata(review_url).await.unwrap();
+        let fourth = scraper.get_review_metadata(review_url).await.unwrap();
+
+        assert_eq!(third.likes.as_deref(), Some("2"));
+        assert_eq!(fourth, third);
+        assert_eq!(scraper.inner.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(scraper.stats.record(true), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn caching_scraper_does_not_cache_failed_scrapes() {
+        let scraper = build_scraper();
+        let profile_url = "https://backloggd.com/u/username1/";
+
+        assert_eq!(
+            scraper.get_profile_pic_url_or_default(profile_url).await,
+            None
+        );
+        assert_eq!(
+            scraper.get_profile_pic_url_or_default(profile_url).await,
+            None
+        );
+        assert_eq!(scraper.inner.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::anyhow;
+use anyhow::Error;
+use base64::prelude::*;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::fmt;
+
+// ChaCha20Poly1305 uses 96-bit nonces
+const NONCE_LENGTH: usize = 12;
+
+/// Encrypts secrets such as webhook tokens before they're written to the database.
+#[derive(Clone)]
+pub struct SecretCipher {
+    cipher: ChaCha20Poly1305,
+}
+
+// Keep the key out of logs
+impl fmt::Debug for SecretCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretCipher").finish_non_exhaustive()
+    }
+}
+
+impl SecretCipher {
+    /// The key is 32 random bytes encoded as base64, e.g. the output of `openssl rand -base64 32`.
+    pub fn from_base64_key(key: &str) -> Result<Self, Error> {
+        let key_bytes = BASE64_STANDARD.decode(key.trim())?;
+
+        if key_bytes.len() != 32 {
+            return Err(anyhow!("Secret key must be 32 bytes long"));
+        }
+
+        return Ok(SecretCipher {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key_bytes)),
+        });
+    }
+
+    /// Returns the base64 encoded nonce followed by the ciphertext.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, Error> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| anyhow!("Unable to encrypt secret"))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend(ciphertext);
+
+        Ok(BASE64_STANDARD.encode(encrypted))
+    }
+
+    pub fn decrypt(&self, encrypted: &str) -> Result<String, Error> {
+        let encrypted_bytes = BASE64_STANDARD.decode(encrypted)?;
+
+        if encrypted_bytes.len() < NONCE_LENGTH {
+            return Err(anyhow!("Encrypted secret is too short"));
+        }
+
+        let (nonce, ciphertext) = encrypted_bytes.split_at(NONCE_LENGTH);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Unable to decrypt secret"))?;
+
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+    const OTHER_KEY: &str = "HxweHRwbGhkYFxYVFBMSERAPDg0MCwoJCAcGBQQDAgE=";
+
+    #[test]
+    fn decrypt_returns_encrypted_plaintext() {
+        let cipher = SecretCipher::from_base64_key(KEY).unwrap();
+
+        let encrypted = cipher.encrypt("webhook-token").unwrap();
+        let actual = cipher.decrypt(&encrypted).unwrap();
+
+        assert_ne!(encrypted, "webhook-token");
+        assert_eq!(actual, "webhook-token");
+    }
+
+    #[test]
+    fn encrypt_uses_new_nonce_each_time() {
+        let cipher = SecretCipher::from_base64_key(KEY).unwrap();
+
+        let first = cipher.encrypt("webhook-token").unwrap();
+        let second = cipher.encrypt("webhook-token").unwrap();
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn decrypt_returns_error_when_key_differs() {
+        let cipher = SecretCipher::from_base64_key(KEY).unwrap();
+        let other_cipher = SecretCipher::from_base64_key(OTHER_KEY).unwrap();
+
+        let encrypted = cipher.encrypt("webhook-token").unwrap();
+
+        assert!(other_cipher.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn from_base64_key_returns_error_when_key_wrong_length() {
+        let actual = SecretCipher::from_base64_key("AAECAwQF");
+
+        assert!(actual.is_err());
+    }
+}
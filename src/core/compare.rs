@@ -0,0 +1,305 @@
+use chrono::NaiveDateTime;
+use poise::serenity_prelude::{Color, CreateEmbed};
+use std::collections::HashMap;
+
+use super::models::StoredReview;
+use super::parser::RssItem;
+
+const DISAGREEMENT_COUNT: usize = 5;
+// Keeps the disagreement table narrow enough not to wrap on mobile
+const TABLE_TITLE_WIDTH: usize = 20;
+const TABLE_USERNAME_WIDTH: usize = 9;
+// Ratings go from half a star to five stars, in half stars
+const MAX_RATING_DIFFERENCE: f32 = 9.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRating {
+    pub title: String,
+    // Half stars out of 10, like RSS ratings
+    pub rating: i8,
+    pub pub_date: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedGame {
+    pub title: String,
+    pub first_rating: i8,
+    pub second_rating: i8,
+}
+
+impl SharedGame {
+    pub fn difference(&self) -> i8 {
+        (self.first_rating - self.second_rating).abs()
+    }
+}
+
+/// Collects a user's ratings by lowercased title from their stored reviews and the reviews
+/// scraped from their feed. When a game was reviewed more than once the latest rating counts, and
+/// unrated reviews are skipped.
+pub fn collect_ratings(stored: &[StoredReview], items: &[RssItem]) -> HashMap<String, GameRating> {
+    let reviews = stored
+        .iter()
+        .map(|review| (&review.title, review.user_rating, review.pub_date))
+        .chain(
+            items
+                .iter()
+                .map(|item| (&item.title, item.user_rating, item.pub_date)),
+        );
+
+    let mut ratings: HashMap<String, GameRating> = HashMap::new();
+
+    for (title, rating, pub_date) in reviews.filter(|(_, rating, _)| *rating > 0) {
+        let key = title.trim().to_lowercase();
+
+        if ratings
+            .get(&key)
+            .is_some_and(|existing| existing.pub_date >= pub_date)
+        {
+            continue;
+        }
+
+        ratings.insert(
+            key,
+            GameRating {
+                title: title.trim().to_string(),
+                rating,
+                pub_date,
+            },
+        );
+    }
+
+    ratings
+}
+
+/// Returns the games both users rated, the biggest disagreements first and ties by title.
+pub fn compare_ratings(
+    first: &HashMap<String, GameRating>,
+    second: &HashMap<String, GameRating>,
+) -> Vec<SharedGame> {
+    let mut shared: Vec<SharedGame> = first
+        .iter()
+        .filter_map(|(key, first_rating)| {
+            second.get(key).map(|second_rating| SharedGame {
+                title: first_rating.title.clone(),
+                first_rating: first_rating.rating,
+                second_rating: second_rating.rating,
+            })
+        })
+        .collect();
+
+    shared.sort_by(|a, b| {
+        b.difference()
+            .cmp(&a.difference())
+            .then_with(|| a.title.cmp(&b.title))
+    });
+
+    shared
+}
+
+/// Returns the average difference between the users' ratings in stars.
+pub fn average_difference(shared: &[SharedGame]) -> Option<f32> {
+    if shared.is_empty() {
+        return None;
+    }
+
+    let total: i32 = shared.iter().map(|game| game.difference() as i32).sum();
+
+    Some(total as f32 / shared.len() as f32 / 2.0)
+}
+
+/// Scores how closely the users' ratings agree from 0 to 100, 100 meaning they rated every
+/// shared game the same.
+pub fn compatibility(shared: &[SharedGame]) -> Option<u32> {
+    let average_difference = average_difference(shared)? * 2.0;
+
+    Some(((1.0 - average_difference / MAX_RATING_DIFFERENCE) * 100.0).round() as u32)
+}
+
+/// Lays the biggest disagreements out as a monospaced table, ratings in stars.
+pub fn format_disagreement_table(
+    first_username: &str,
+    second_username: &str,
+    shared: &[SharedGame],
+) -> String {
+    let stars = |rating: i8| format!("{:.1}", rating as f32 / 2.0);
+
+    let mut lines = vec![format!(
+        "{:<title$} {:>user$} {:>user$}",
+        "Game",
+        truncate(first_username, TABLE_USERNAME_WIDTH),
+        truncate(second_username, TABLE_USERNAME_WIDTH),
+        title = TABLE_TITLE_WIDTH,
+        user = TABLE_USERNAME_WIDTH
+    )];
+
+    for game in shared.iter().take(DISAGREEMENT_COUNT) {
+        lines.push(format!(
+            "{:<title$} {:>user$} {:>user$}",
+            truncate(&game.title, TABLE_TITLE_WIDTH),
+            stars(game.first_rating),
+            stars(game.second_rating),
+            title = TABLE_TITLE_WIDTH,
+            user = TABLE_USERNAME_WIDTH
+        ));
+    }
+
+    format!("```\n{}\n```", lines.join("\n"))
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    let truncated: String = text.chars().take(width - 1).collect();
+    format!("{}…", truncated)
+}
+
+pub fn build_compare_embed(
+    first_username: &str,
+    second_username: &str,
+    shared: &[SharedGame],
+) -> CreateEmbed {
+    let embed = CreateEmbed::new()
+        .color(Color::from_rgb(252, 99, 153))
+        .title(format!("{} vs {}", first_username, second_username));
+
+    let (Some(average_difference), Some(compatibility)) =
+        (average_difference(shared), compatibility(shared))
+    else {
+        return embed.description("They haven't rated any of the same games yet");
+    };
+
+    let mut embed = embed
+        .description(format!("{} games rated by both", shared.len()))
+        .field("Compatibility", format!("{}%", compatibility), true)
+        .field(
+            "Average difference",
+            format!("{:.1} ★", average_difference),
+            true,
+        );
+
+    if shared.iter().any(|game| game.difference() > 0) {
+        embed = embed.field(
+            "Biggest disagreements",
+            format_disagreement_table(
+                first_username,
+                second_username,
+                &shared
+                    .iter()
+                    .filter(|game| game.difference() > 0)
+                    .cloned()
+                    .collect::<Vec<SharedGame>>(),
+            ),
+            false,
+        );
+    }
+
+    embed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::build_test_review;
+    use crate::core::parser;
+    use chrono::NaiveDate;
+
+    const FIRST_FEED_XML: &str = include_str!("fixtures/feed_username1.xml");
+    const SECOND_FEED_XML: &str = include_str!("fixtures/feed_username2.xml");
+
+    fn build_stored_review(title: &str, rating: i8, day: u32) -> StoredReview {
+        StoredReview {
+            guid: format!("{}-{}", title, day),
+            pub_date: NaiveDate::from_ymd_opt(2024, 5, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            ..build_test_review(title, rating)
+        }
+    }
+
+    fn build_fixture_comparison() -> Vec<SharedGame> {
+        let first_items = parser::parse_rss_xml(FIRST_FEED_XML).unwrap().channel.item;
+        let second_items = parser::parse_rss_xml(SECOND_FEED_XML).unwrap().channel.item;
+
+        // Older than the feed's Celeste review, and a game that's left the feed
+        let stored = vec![
+            build_stored_review("Celeste", 2, 1),
+            build_stored_review("Outer Wilds", 6, 1),
+        ];
+
+        compare_ratings(
+            &collect_ratings(&stored, &first_items),
+            &collect_ratings(&[], &second_items),
+        )
+    }
+
+    #[test]
+    fn collect_ratings_keeps_latest_rated_review() {
+        let items = parser::parse_rss_xml(FIRST_FEED_XML).unwrap().channel.item;
+        let stored = vec![build_stored_review("Celeste", 2, 1)];
+
+        let actual = collect_ratings(&stored, &items);
+
+        assert_eq!(actual["celeste"].rating, 9);
+        assert_eq!(actual["hades"].title, "Hades");
+        assert!(!actual.contains_key("tetris"));
+        assert_eq!(actual.len(), 4);
+    }
+
+    #[test]
+    fn compare_ratings_orders_by_biggest_disagreement() {
+        let actual = build_fixture_comparison();
+
+        assert_eq!(
+            actual
+                .iter()
+                .map(|game| (game.title.as_str(), game.first_rating, game.second_rating))
+                .collect::<Vec<(&str, i8, i8)>>(),
+            vec![
+                ("Starfield", 3, 9),
+                ("Celeste", 9, 4),
+                ("Outer Wilds", 6, 10),
+                ("Hades", 10, 9)
+            ]
+        );
+    }
+
+    #[test]
+    fn average_difference_and_compatibility_use_shared_games() {
+        let shared = build_fixture_comparison();
+
+        assert_eq!(average_difference(&shared), Some(2.0));
+        assert_eq!(compatibility(&shared), Some(56));
+        assert_eq!(average_difference(&[]), None);
+        assert_eq!(compatibility(&[]), None);
+    }
+
+    #[test]
+    fn compatibility_is_full_for_identical_ratings() {
+        let shared = vec![SharedGame {
+            title: "Hades".to_string(),
+            first_rating: 7,
+            second_rating: 7,
+        }];
+
+        assert_eq!(compatibility(&shared), Some(100));
+    }
+
+    #[test]
+    fn format_disagreement_table_truncates_long_titles() {
+        let shared = vec![SharedGame {
+            title: "The Legend of Zelda: Tears of the Kingdom".to_string(),
+            first_rating: 10,
+            second_rating: 3,
+        }];
+
+        let actual = format_disagreement_table("username1", "a_long_username", &shared);
+
+        assert_eq!(
+            actual,
+            "```\nGame                 username1 a_long_u…\nThe Legend of Zelda…       5.0       1.5\n```"
+        );
+    }
+}
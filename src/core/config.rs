@@ -4,3 +4,11 @@ pub fn get_docker_file_secret(path: &str) -> Result<String, anyhow::Error> {
     let secret = fs::read_to_string(path).expect("Unable to read secret at {path}");
     return Ok(secret);
 }
+
+pub fn get_optional_docker_file_secret(path: &str) -> Option<String> {
+    return fs::read_to_string(path).ok();
+}
+
+pub fn get_env_flag(name: &str) -> bool {
+    return std::env::var(name).is_ok_and(|value| value.trim().eq_ignore_ascii_case("true"));
+}
@@ -1,21 +1,146 @@
 use anyhow::Error;
-use chrono::{DateTime, NaiveDateTime};
+use chrono::{DateTime, NaiveDateTime, NaiveTime};
+
+use super::models::Mention;
 
 pub fn parse_backloggd_rss_date(date: &str) -> Result<NaiveDateTime, Error> {
-    let backloggd_date = DateTime::parse_from_rfc2822(&date)?;
+    let backloggd_date = DateTime::parse_from_rfc2822(date)?;
     Ok(backloggd_date.naive_utc())
 }
 
 pub fn parse_sqlite_date(date: &str) -> Result<NaiveDateTime, Error> {
-    let sqlite_date = NaiveDateTime::parse_from_str(&date, "%Y-%m-%dT%H:%M:%S")?;
+    let sqlite_date = NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S")?;
     Ok(sqlite_date)
 }
 
+pub fn format_sqlite_date(date: &NaiveDateTime) -> String {
+    return date.format("%Y-%m-%dT%H:%M:%S").to_string();
+}
+
 pub fn get_sqlite_now() -> String {
-    return chrono::Utc::now()
-        .naive_utc()
-        .format("%Y-%m-%dT%H:%M:%S")
-        .to_string();
+    return format_sqlite_date(&chrono::Utc::now().naive_utc());
+}
+
+pub fn parse_digest_time(time: &str) -> Result<NaiveTime, Error> {
+    let digest_time = NaiveTime::parse_from_str(time.trim(), "%H:%M")?;
+    Ok(digest_time)
+}
+
+/// Backloggd ratings are stored as half stars, so a rating of 7 is three and a half stars.
+pub fn rating_to_stars(rating: i8) -> String {
+    if rating <= 0 {
+        return "".to_string();
+    }
+
+    let mut stars = "★".repeat((rating / 2) as usize);
+
+    if rating % 2 == 1 {
+        stars.push('½');
+    }
+
+    stars
+}
+
+// TODO: truncate a bit more nicely, ending on a word not potentially halfway through one
+pub fn truncate_review(description: &str) -> String {
+    match description.char_indices().nth(1000) {
+        Some((end, _)) => format!("{}...", &description[..end]),
+        None => description.to_string(),
+    }
+}
+
+/// Groups ratings into whole star buckets so they can be matched against forum tag names.
+pub fn rating_to_tag_name(rating: i8) -> String {
+    if rating <= 0 {
+        return "Unrated".to_string();
+    }
+
+    "★".repeat(((rating + 1) / 2) as usize)
+}
+
+/// Draws the number of ratings for each half star as a bar chart from ½ to ★★★★★, scaled to the
+/// most common rating.
+pub fn format_rating_distribution(counts: &[u32]) -> Option<String> {
+    let bars = format_bar_chart(counts)?;
+
+    Some(format!("½ {} ★★★★★", bars))
+}
+
+/// Draws one block character per count, scaled to the largest count. Returns None when there's
+/// nothing to draw.
+pub fn format_bar_chart(counts: &[u32]) -> Option<String> {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let max = *counts.iter().max()?;
+
+    if max == 0 {
+        return None;
+    }
+
+    let bars = counts
+        .iter()
+        .map(|count| {
+            let height = (*count as u64 * (BARS.len() as u64 - 1)).div_ceil(max as u64);
+            BARS[height as usize]
+        })
+        .collect();
+
+    Some(bars)
+}
+
+/// Builds a LIKE pattern matching any text containing `text`, with the wildcards in `text`
+/// escaped by a backslash.
+pub fn to_like_pattern(text: &str) -> String {
+    let escaped = text
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{}%", escaped)
+}
+
+/// Backloggd shows counts with thousands separators, e.g. 1,048.
+pub fn parse_count(count: &str) -> Option<i64> {
+    count.trim().replace(',', "").parse().ok()
+}
+
+pub fn format_average_rating(average_rating: f32) -> String {
+    format!(
+        "{:.1} {}",
+        average_rating,
+        rating_to_stars((average_rating * 2.0).round() as i8)
+    )
+}
+
+pub fn format_thread_name(title: &str) -> String {
+    // Discord rejects thread names longer than 100 characters
+    let mut name: String = title.trim().chars().take(100).collect();
+
+    if name.is_empty() {
+        name.push_str("Review");
+    }
+
+    name
+}
+
+pub fn format_mentions(mention: Option<&Mention>, user_ids: &[u64]) -> String {
+    let mut mentions: Vec<String> = vec![];
+
+    match mention {
+        Some(Mention::Role(id)) => mentions.push(format!("<@&{}>", id)),
+        Some(Mention::User(id)) => mentions.push(format!("<@{}>", id)),
+        Some(Mention::Here) => mentions.push("@here".to_string()),
+        None => {}
+    }
+
+    for id in user_ids {
+        if mention != Some(&Mention::User(*id)) {
+            mentions.push(format!("<@{}>", id));
+        }
+    }
+
+    mentions.join(" ")
 }
 
 #[cfg(test)]
@@ -25,8 +150,8 @@ mod tests {
 
     #[test]
     fn parse_backloggd_date_returns_valid_datetime() {
-        let date = NaiveDate::from_ymd_opt(2024, 05, 04).unwrap();
-        let time = NaiveTime::from_hms_opt(01, 05, 21).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 5, 4).unwrap();
+        let time = NaiveTime::from_hms_opt(1, 5, 21).unwrap();
 
         let expected = NaiveDateTime::new(date, time);
         let actual = parse_backloggd_rss_date("Sat, 04 May 2024 01:05:21 +0000");
@@ -43,10 +168,119 @@ mod tests {
         }
     }
 
+    #[test]
+    fn format_sqlite_date_round_trips_through_parse_sqlite_date() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap();
+        let time = NaiveTime::from_hms_opt(18, 30, 0).unwrap();
+        let expected = NaiveDateTime::new(date, time);
+
+        let formatted = format_sqlite_date(&expected);
+
+        assert_eq!(formatted, "2025-03-09T18:30:00");
+        assert_eq!(parse_sqlite_date(&formatted).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_digest_time_returns_valid_time() {
+        let expected = NaiveTime::from_hms_opt(18, 30, 0).unwrap();
+        let actual = parse_digest_time(" 18:30 ");
+
+        assert_eq!(actual.unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_digest_time_returns_error_when_time_invalid() {
+        assert!(parse_digest_time("25:00").is_err());
+        assert!(parse_digest_time("6pm").is_err());
+        assert!(parse_digest_time("").is_err());
+    }
+
+    #[test]
+    fn rating_to_stars_returns_half_stars() {
+        assert_eq!(rating_to_stars(10), "★★★★★");
+        assert_eq!(rating_to_stars(7), "★★★½");
+        assert_eq!(rating_to_stars(1), "½");
+    }
+
+    #[test]
+    fn rating_to_stars_returns_empty_when_unrated() {
+        assert_eq!(rating_to_stars(0), "");
+        assert_eq!(rating_to_stars(-1), "");
+    }
+
+    #[test]
+    fn truncate_review_keeps_first_thousand_characters() {
+        let description = "é".repeat(1500);
+
+        let actual = truncate_review(&description);
+
+        assert_eq!(actual.chars().count(), 1003);
+        assert!(actual.ends_with("é..."));
+    }
+
+    #[test]
+    fn truncate_review_keeps_short_reviews_whole() {
+        let description = "é".repeat(1000);
+
+        let actual = truncate_review(&description);
+
+        assert_eq!(actual, description);
+    }
+
+    #[test]
+    fn rating_to_tag_name_rounds_up_to_whole_stars() {
+        assert_eq!(rating_to_tag_name(10), "★★★★★");
+        assert_eq!(rating_to_tag_name(9), "★★★★★");
+        assert_eq!(rating_to_tag_name(4), "★★");
+        assert_eq!(rating_to_tag_name(1), "★");
+    }
+
+    #[test]
+    fn rating_to_tag_name_returns_unrated() {
+        assert_eq!(rating_to_tag_name(0), "Unrated");
+    }
+
+    #[test]
+    fn format_thread_name_truncates_long_titles() {
+        let title = "a".repeat(150);
+
+        assert_eq!(format_thread_name(&title).chars().count(), 100);
+    }
+
+    #[test]
+    fn format_thread_name_returns_default_when_title_empty() {
+        assert_eq!(format_thread_name("  "), "Review");
+        assert_eq!(format_thread_name(" Outer Wilds "), "Outer Wilds");
+    }
+
+    #[test]
+    fn format_mentions_returns_subscription_mention_then_users() {
+        let actual = format_mentions(Some(&Mention::Role(10)), &[20, 30]);
+
+        assert_eq!(actual, "<@&10> <@20> <@30>");
+    }
+
+    #[test]
+    fn format_mentions_skips_user_already_mentioned() {
+        let actual = format_mentions(Some(&Mention::User(20)), &[20]);
+
+        assert_eq!(actual, "<@20>");
+    }
+
+    #[test]
+    fn format_mentions_returns_here() {
+        assert_eq!(format_mentions(Some(&Mention::Here), &[]), "@here");
+    }
+
+    #[test]
+    fn format_mentions_returns_empty_when_no_mentions() {
+        assert_eq!(format_mentions(None, &[]), "");
+    }
+
     #[test]
     fn parse_sqlite_date_returns_valid_datetime() {
-        let date = NaiveDate::from_ymd_opt(2025, 01, 01).unwrap();
-        let time = NaiveTime::from_hms_opt(01, 05, 21).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let time = NaiveTime::from_hms_opt(1, 5, 21).unwrap();
 
         let expected = NaiveDateTime::new(date, time);
         let actual = parse_sqlite_date("2025-01-01T01:05:21");
@@ -62,4 +296,36 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn format_rating_distribution_scales_to_most_common_rating() {
+        let actual = format_rating_distribution(&[0, 1, 2, 4, 8, 8, 4, 2, 1, 0]);
+
+        assert_eq!(actual, Some("½ ▁▂▃▅██▅▃▂▁ ★★★★★".to_string()));
+    }
+
+    #[test]
+    fn format_rating_distribution_returns_none_without_ratings() {
+        assert_eq!(format_rating_distribution(&[]), None);
+        assert_eq!(format_rating_distribution(&[0, 0, 0]), None);
+    }
+
+    #[test]
+    fn format_average_rating_rounds_to_half_stars() {
+        assert_eq!(format_average_rating(4.4), "4.4 ★★★★½");
+        assert_eq!(format_average_rating(3.2), "3.2 ★★★");
+    }
+
+    #[test]
+    fn parse_count_strips_thousands_separators() {
+        assert_eq!(parse_count("1,048"), Some(1048));
+        assert_eq!(parse_count(" 12 "), Some(12));
+        assert_eq!(parse_count("Likes"), None);
+    }
+
+    #[test]
+    fn to_like_pattern_escapes_wildcards() {
+        assert_eq!(to_like_pattern(" Hades "), "%Hades%");
+        assert_eq!(to_like_pattern("100%_"), "%100\\%\\_%");
+    }
 }
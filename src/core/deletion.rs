@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use super::models::DeliveredReview;
+use super::parser::RssItem;
+
+// A review has to be missing from this many polls in a row before its posts are removed, so a
+// feed that briefly comes back incomplete doesn't wipe out a channel.
+pub const MISSING_POLL_THRESHOLD: i64 = 3;
+
+/// Returns the delivered reviews that should still be in the feed but aren't. The feed only holds
+/// the most recent reviews, so anything older than its oldest item has just scrolled out of it.
+pub fn find_missing_reviews<'a>(
+    delivered_reviews: &'a [DeliveredReview],
+    items: &[RssItem],
+) -> Vec<&'a DeliveredReview> {
+    // An empty feed is more likely a glitch than every review being deleted at once
+    let oldest_pub_date = match items.iter().map(|item| item.pub_date).min() {
+        Some(pub_date) => pub_date,
+        None => return vec![],
+    };
+
+    let guids: HashSet<&str> = items.iter().map(|item| item.guid.as_str()).collect();
+
+    delivered_reviews
+        .iter()
+        .filter(|review| review.pub_date >= oldest_pub_date)
+        .filter(|review| !guids.contains(review.guid.as_str()))
+        .collect()
+}
+
+pub fn should_remove(missing_polls: i64, is_review_deleted: bool) -> bool {
+    is_review_deleted || missing_polls >= MISSING_POLL_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::build_test_item;
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn date(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn build_delivered_review(guid: &str, day: u32) -> DeliveredReview {
+        DeliveredReview {
+            id: 0,
+            guid: guid.to_string(),
+            rss_feed_id: 0,
+            channel_id: 0,
+            message_id: 0,
+            via_webhook: false,
+            link: "https://backloggd.com/u/username1/review/1/".to_string(),
+            pub_date: date(day),
+            footer: "".to_string(),
+            description: "Description".to_string(),
+            missing_polls: 0,
+        }
+    }
+
+    #[test]
+    fn find_missing_reviews_returns_reviews_absent_from_feed() {
+        let items = vec![
+            build_test_item("review-1", 1),
+            build_test_item("review-3", 3),
+        ];
+        let delivered_reviews = vec![
+            build_delivered_review("review-1", 1),
+            build_delivered_review("review-2", 2),
+            build_delivered_review("review-3", 3),
+        ];
+
+        let actual = find_missing_reviews(&delivered_reviews, &items);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].guid, "review-2");
+    }
+
+    #[test]
+    fn find_missing_reviews_ignores_reviews_older_than_feed() {
+        let items = vec![build_test_item("review-5", 5)];
+        let delivered_reviews = vec![build_delivered_review("review-1", 1)];
+
+        let actual = find_missing_reviews(&delivered_reviews, &items);
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn find_missing_reviews_returns_nothing_when_feed_empty() {
+        let delivered_reviews = vec![build_delivered_review("review-1", 1)];
+
+        let actual = find_missing_reviews(&delivered_reviews, &[]);
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn should_remove_waits_for_threshold() {
+        assert!(!should_remove(1, false));
+        assert!(!should_remove(MISSING_POLL_THRESHOLD - 1, false));
+        assert!(should_remove(MISSING_POLL_THRESHOLD, false));
+    }
+
+    #[test]
+    fn should_remove_returns_true_when_review_page_gone() {
+        assert!(should_remove(1, true));
+    }
+}
@@ -0,0 +1,243 @@
+use chrono::{Datelike, NaiveDateTime, TimeZone};
+use poise::serenity_prelude::{Color, CreateEmbed};
+
+use super::converter;
+use super::models::{DigestFrequency, DigestItem, DigestSchedule};
+
+// Discord rejects embeds with a description longer than this
+const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+
+/// Returns the first UTC time after `after` at which the digest should be posted, or None if the
+/// schedule can't be resolved in its timezone.
+pub fn next_run(schedule: &DigestSchedule, after: NaiveDateTime) -> Option<NaiveDateTime> {
+    let local_after = schedule.timezone.from_utc_datetime(&after);
+    let mut date = local_after.date_naive();
+
+    // A weekly digest is at most 7 days out, the extra day covers local times that don't exist
+    // because of a DST transition.
+    for _ in 0..9 {
+        let is_scheduled_day = match schedule.frequency {
+            DigestFrequency::Daily => true,
+            DigestFrequency::Weekly => Some(date.weekday()) == schedule.weekday,
+        };
+
+        if is_scheduled_day {
+            if let Some(local_run) = schedule
+                .timezone
+                .from_local_datetime(&date.and_time(schedule.time))
+                .earliest()
+            {
+                let run = local_run.naive_utc();
+                if run > after {
+                    return Some(run);
+                }
+            }
+        }
+
+        date = date.succ_opt()?;
+    }
+
+    None
+}
+
+pub fn build_digest_embed(frequency: &DigestFrequency, items: &[DigestItem]) -> CreateEmbed {
+    return CreateEmbed::new()
+        .color(Color::from_rgb(252, 99, 153))
+        .title(build_digest_title(frequency, items))
+        .description(build_digest_description(items));
+}
+
+/// Counts the reviews listed in this digest, the ones that didn't fit are counted in the
+/// description instead.
+pub fn build_digest_title(frequency: &DigestFrequency, items: &[DigestItem]) -> String {
+    let title = match frequency {
+        DigestFrequency::Daily => "Daily review digest",
+        DigestFrequency::Weekly => "Weekly review digest",
+    };
+
+    format!("{} • {} new reviews", title, count_fitting_items(items))
+}
+
+/// Lists one review per line, cutting the list short once it would no longer fit in an embed.
+pub fn build_digest_description(items: &[DigestItem]) -> String {
+    let shown = count_fitting_items(items);
+    let mut description: String = items[..shown].iter().map(build_digest_line).collect();
+
+    if shown < items.len() {
+        description.push_str(&build_overflow_line(items.len() - shown));
+    }
+
+    description
+}
+
+/// How many items fit in one digest, the ones after them stay queued for the next digest.
+pub fn count_fitting_items(items: &[DigestItem]) -> usize {
+    let mut length = 0;
+
+    for (index, item) in items.iter().enumerate() {
+        let line_length = build_digest_line(item).chars().count();
+
+        let remaining = items.len() - index - 1;
+        let overflow = if remaining > 0 {
+            build_overflow_line(remaining).chars().count()
+        } else {
+            0
+        };
+
+        if length + line_length + overflow > EMBED_DESCRIPTION_LIMIT {
+            return index;
+        }
+
+        length += line_length;
+    }
+
+    items.len()
+}
+
+fn build_overflow_line(remaining: usize) -> String {
+    format!("…and {} more in the next digest", remaining)
+}
+
+fn build_digest_line(item: &DigestItem) -> String {
+    let stars = converter::rating_to_stars(item.user_rating);
+
+    if stars.is_empty() {
+        return format!("**[{}]({})** by {}\n", item.title, item.link, item.reviewer);
+    }
+
+    return format!(
+        "**[{}]({})** by {} • {}\n",
+        item.title, item.link, item.reviewer, stars
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveTime, Weekday};
+
+    fn build_schedule(frequency: DigestFrequency, weekday: Option<Weekday>) -> DigestSchedule {
+        DigestSchedule {
+            channel_id: 0,
+            frequency,
+            weekday,
+            time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            timezone: chrono_tz::Europe::London,
+            next_run: NaiveDateTime::default(),
+        }
+    }
+
+    fn build_item(title: &str, user_rating: i8) -> DigestItem {
+        DigestItem {
+            id: 0,
+            channel_id: 0,
+            title: title.to_string(),
+            link: "https://backloggd.com/u/username1/review/1/".to_string(),
+            reviewer: "username1".to_string(),
+            user_rating,
+            pub_date: NaiveDateTime::default(),
+        }
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn next_run_returns_same_day_when_time_not_passed() {
+        let schedule = build_schedule(DigestFrequency::Daily, None);
+
+        // London is on GMT in January
+        let actual = next_run(&schedule, utc(2025, 1, 10, 12, 0));
+
+        assert_eq!(actual, Some(utc(2025, 1, 10, 18, 0)));
+    }
+
+    #[test]
+    fn next_run_returns_next_day_when_time_passed() {
+        let schedule = build_schedule(DigestFrequency::Daily, None);
+
+        // London is on BST in July, so 18:00 local is 17:00 UTC
+        let actual = next_run(&schedule, utc(2025, 7, 10, 17, 0));
+
+        assert_eq!(actual, Some(utc(2025, 7, 11, 17, 0)));
+    }
+
+    #[test]
+    fn next_run_returns_scheduled_weekday_when_weekly() {
+        let schedule = build_schedule(DigestFrequency::Weekly, Some(Weekday::Sun));
+
+        // 2025-01-10 is a Friday
+        let actual = next_run(&schedule, utc(2025, 1, 10, 12, 0));
+
+        assert_eq!(actual, Some(utc(2025, 1, 12, 18, 0)));
+    }
+
+    #[test]
+    fn next_run_returns_following_week_when_weekly_time_passed() {
+        let schedule = build_schedule(DigestFrequency::Weekly, Some(Weekday::Fri));
+
+        let actual = next_run(&schedule, utc(2025, 1, 10, 18, 0));
+
+        assert_eq!(actual, Some(utc(2025, 1, 17, 18, 0)));
+    }
+
+    #[test]
+    fn build_digest_description_lists_every_item() {
+        let items = vec![build_item("Item1", 7), build_item("Item2", 0)];
+
+        let actual = build_digest_description(&items);
+
+        assert_eq!(
+            actual,
+            "**[Item1](https://backloggd.com/u/username1/review/1/)** by username1 • ★★★½\n\
+             **[Item2](https://backloggd.com/u/username1/review/1/)** by username1\n"
+        );
+    }
+
+    #[test]
+    fn build_digest_description_truncates_to_embed_limit() {
+        let items: Vec<DigestItem> = (0..200)
+            .map(|index| build_item(&format!("Item{}", index), 10))
+            .collect();
+
+        let actual = build_digest_description(&items);
+        let shown = count_fitting_items(&items);
+
+        assert!(shown < items.len());
+        assert!(actual.chars().count() <= EMBED_DESCRIPTION_LIMIT);
+        assert_eq!(actual.lines().count(), shown + 1);
+        assert!(actual.ends_with(&format!("…and {} more in the next digest", 200 - shown)));
+    }
+
+    #[test]
+    fn build_digest_title_counts_listed_items() {
+        let items: Vec<DigestItem> = (0..200)
+            .map(|index| build_item(&format!("Item{}", index), 10))
+            .collect();
+
+        let actual = build_digest_title(&DigestFrequency::Daily, &items);
+
+        assert_eq!(
+            actual,
+            format!(
+                "Daily review digest • {} new reviews",
+                count_fitting_items(&items)
+            )
+        );
+        assert_eq!(
+            build_digest_title(&DigestFrequency::Weekly, &items[..2]),
+            "Weekly review digest • 2 new reviews"
+        );
+    }
+
+    #[test]
+    fn count_fitting_items_returns_all_when_they_fit() {
+        let items = vec![build_item("Item1", 7), build_item("Item2", 0)];
+
+        assert_eq!(count_fitting_items(&items), 2);
+    }
+}
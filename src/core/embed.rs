@@ -0,0 +1,301 @@
+use poise::serenity_prelude::{Color, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter};
+
+use super::converter;
+use super::models::StoredReview;
+use super::parser::{RssChannel, RssItem};
+use super::scraper::{self, GameDetails, GameLog, PagePreview, ReviewMetadata, UserProfile};
+use super::unfurl::BackloggdLink;
+
+// Used as the author icon when the reviewer's avatar can't be scraped
+pub const DEFAULT_PROFILE_PIC_URL: &str = "https://backloggd.com/favicon.ico";
+
+/// Builds the embed a review is posted with, shared by the publisher and /preview so a preview
+/// looks exactly like the real post.
+pub fn build_review_embed(
+    channel: &RssChannel,
+    rss_item: &RssItem,
+    profile_pic_url: &str,
+    footer_text: &str,
+) -> CreateEmbed {
+    let author = CreateEmbedAuthor::new(&rss_item.reviewer)
+        .url(&channel.link)
+        .icon_url(profile_pic_url);
+
+    let footer = CreateEmbedFooter::new(footer_text);
+
+    let truncated_review = converter::truncate_review(&rss_item.description);
+
+    return CreateEmbed::new()
+        .url(&rss_item.link)
+        .color(Color::from_rgb(252, 99, 153))
+        .title(&rss_item.title)
+        .thumbnail(&rss_item.image.url)
+        .description(truncated_review)
+        .footer(footer)
+        .author(author);
+}
+
+/// Builds a review embed from the stored copy of a review, looking like the publisher's post.
+pub fn build_stored_review_embed(review: &StoredReview, profile_pic_url: &str) -> CreateEmbed {
+    let author = CreateEmbedAuthor::new(&review.reviewer)
+        .url(scraper::build_profile_url(&review.reviewer))
+        .icon_url(profile_pic_url);
+
+    let metadata = ReviewMetadata {
+        likes: review.likes.map(|likes| likes.to_string()),
+        comments: review.comments.map(|comments| comments.to_string()),
+        status: review.status.clone(),
+    };
+
+    let footer = CreateEmbedFooter::new(build_footer(Some(metadata)));
+
+    return CreateEmbed::new()
+        .url(&review.link)
+        .color(Color::from_rgb(252, 99, 153))
+        .title(&review.title)
+        .thumbnail(&review.image_url)
+        .description(converter::truncate_review(&review.description))
+        .footer(footer)
+        .author(author);
+}
+
+/// Builds a review embed from the review's page, looking like the publisher's post, for reviews
+/// that have already left the user's feed.
+pub fn build_scraped_review_embed(
+    username: &str,
+    log: &GameLog,
+    review_url: &str,
+    preview: PagePreview,
+) -> CreateEmbed {
+    let avatar_url = preview
+        .avatar_url
+        .as_deref()
+        .unwrap_or(DEFAULT_PROFILE_PIC_URL);
+
+    let author = CreateEmbedAuthor::new(username)
+        .url(scraper::build_profile_url(username))
+        .icon_url(avatar_url);
+
+    let footer = CreateEmbedFooter::new(build_footer(Some(preview.metadata)));
+
+    let mut embed = CreateEmbed::new()
+        .url(review_url)
+        .color(Color::from_rgb(252, 99, 153))
+        .title(&log.title)
+        .description(converter::truncate_review(
+            preview.description.as_deref().unwrap_or_default(),
+        ))
+        .footer(footer)
+        .author(author);
+
+    if let Some(image_url) = &preview.image_url {
+        embed = embed.thumbnail(image_url);
+    }
+
+    embed
+}
+
+/// Builds the embed for a Backloggd link pasted in chat. Reviews look like the publisher's posts,
+/// games and profiles show their cover or avatar with the page description.
+pub fn build_link_embed(link: &BackloggdLink, preview: PagePreview) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .url(link.url())
+        .color(Color::from_rgb(252, 99, 153));
+
+    if let Some(title) = &preview.title {
+        embed = embed.title(title);
+    }
+
+    if let Some(description) = &preview.description {
+        embed = embed.description(converter::truncate_review(description));
+    }
+
+    match link {
+        BackloggdLink::Review { username, .. } => {
+            let avatar_url = preview
+                .avatar_url
+                .as_deref()
+                .unwrap_or(DEFAULT_PROFILE_PIC_URL);
+
+            let author = CreateEmbedAuthor::new(username)
+                .url(scraper::build_profile_url(username))
+                .icon_url(avatar_url);
+
+            embed = embed
+                .author(author)
+                .footer(CreateEmbedFooter::new(build_footer(Some(preview.metadata))));
+
+            if let Some(image_url) = &preview.image_url {
+                embed = embed.thumbnail(image_url);
+            }
+        }
+        BackloggdLink::Game { .. } => {
+            if let Some(image_url) = &preview.image_url {
+                embed = embed.thumbnail(image_url);
+            }
+        }
+        BackloggdLink::User { .. } => {
+            if let Some(avatar_url) = &preview.avatar_url {
+                embed = embed.thumbnail(avatar_url);
+            }
+        }
+    }
+
+    embed
+}
+
+pub fn build_game_embed(game: &GameDetails) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .url(&game.url)
+        .color(Color::from_rgb(252, 99, 153))
+        .title(&game.title);
+
+    if let Some(cover_url) = &game.cover_url {
+        embed = embed.thumbnail(cover_url);
+    }
+
+    if let Some(release_date) = &game.release_date {
+        embed = embed.field("Released", release_date, true);
+    }
+
+    if let Some(average_rating) = game.average_rating {
+        embed = embed.field(
+            "Average rating",
+            converter::format_average_rating(average_rating),
+            true,
+        );
+    }
+
+    if !game.platforms.is_empty() {
+        embed = embed.field("Platforms", game.platforms.join(", "), false);
+    }
+
+    if let Some(distribution) = converter::format_rating_distribution(&game.rating_distribution) {
+        embed = embed.field("Ratings", distribution, false);
+    }
+
+    let plays = [
+        ("Played", &game.plays.played),
+        ("Playing", &game.plays.playing),
+        ("Backlogs", &game.plays.backlogs),
+        ("Wishlists", &game.plays.wishlists),
+    ];
+
+    for (name, count) in plays {
+        if let Some(count) = count {
+            embed = embed.field(name, count, true);
+        }
+    }
+
+    embed
+}
+
+pub fn build_user_embed(profile: &UserProfile) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .url(&profile.url)
+        .color(Color::from_rgb(252, 99, 153))
+        .title(&profile.username);
+
+    if let Some(avatar_url) = &profile.avatar_url {
+        embed = embed.thumbnail(avatar_url);
+    }
+
+    if let Some(bio) = &profile.bio {
+        embed = embed.description(converter::truncate_review(bio));
+    }
+
+    let stats = [
+        ("Games played", &profile.games_played),
+        ("Backlog", &profile.backlog),
+        ("Reviews", &profile.reviews),
+    ];
+
+    for (name, count) in stats {
+        if let Some(count) = count {
+            embed = embed.field(name, count, true);
+        }
+    }
+
+    if !profile.favorite_games.is_empty() {
+        embed = embed.field("Favorite games", profile.favorite_games.join("\n"), false);
+    }
+
+    if !profile.playing.is_empty() {
+        embed = embed.field("Currently playing", profile.playing.join("\n"), false);
+    }
+
+    embed
+}
+
+/// Shown by /review when the user logged a game without reviewing it.
+pub fn build_game_log_embed(username: &str, avatar_url: &str, log: &GameLog) -> CreateEmbed {
+    let author = CreateEmbedAuthor::new(username)
+        .url(scraper::build_profile_url(username))
+        .icon_url(avatar_url);
+
+    let mut embed = CreateEmbed::new()
+        .url(&log.url)
+        .color(Color::from_rgb(252, 99, 153))
+        .title(&log.title)
+        .description(format!("{} hasn't reviewed this game", username))
+        .author(author);
+
+    if let Some(rating) = log.rating {
+        embed = embed.field("Rating", converter::rating_to_stars(rating), true);
+    }
+
+    if let Some(status) = &log.status {
+        embed = embed.field("Status", status, true);
+    }
+
+    embed
+}
+
+pub fn build_footer(review_metadata: Option<ReviewMetadata>) -> String {
+    if let Some(metadata) = review_metadata {
+        let mut footer = "".to_string();
+
+        if let Some(status) = metadata.status {
+            footer.push_str(&format!("{} • ", status));
+        }
+
+        if let Some(likes) = metadata.likes {
+            footer.push_str(&format!("🩷 {} • ", likes));
+        }
+
+        if let Some(comments) = metadata.comments {
+            footer.push_str(&format!("💬 {}", comments));
+        }
+
+        // TODO: properly handle separators
+
+        return footer;
+    }
+
+    "".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_footer_returns_all_metadata() {
+        let metadata = ReviewMetadata {
+            likes: Some("4".to_string()),
+            comments: Some("2".to_string()),
+            status: Some("Completed".to_string()),
+        };
+
+        let actual = build_footer(Some(metadata));
+
+        assert_eq!(actual, "Completed • 🩷 4 • 💬 2");
+    }
+
+    #[test]
+    fn build_footer_returns_empty_without_metadata() {
+        let actual = build_footer(None);
+
+        assert_eq!(actual, "");
+    }
+}
@@ -0,0 +1,671 @@
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{instrument, warn};
+
+/// Timeouts, retries and throttling for requests to Backloggd.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub user_agent: String,
+    pub connect_timeout: Duration,
+    // How long to wait for each read of the response, rather than the whole response
+    pub read_timeout: Duration,
+    // Retries after the first attempt, so a request is sent at most max_retries + 1 times
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    // Also the longest Retry-After that's waited for, longer ones give up straight away
+    pub max_backoff: Duration,
+    // Per host, 0 or less turns rate limiting off
+    pub requests_per_second: f64,
+    // Requests that can be sent at once after a quiet period before the rate limit kicks in
+    pub burst: u32,
+    // Across every host
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: build_user_agent(None),
+            connect_timeout: Duration::from_secs(5),
+            read_timeout: Duration::from_secs(15),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            requests_per_second: 2.0,
+            burst: 4,
+            max_concurrent_requests: 4,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Reads the HTTP_* environment variables listed in the README, keeping the default for any
+    /// that aren't set or aren't numbers.
+    pub fn from_env() -> Self {
+        let read = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+
+        let mut config = Self::default();
+
+        if let Ok(contact) = std::env::var("HTTP_CONTACT") {
+            config.user_agent = build_user_agent(Some(&contact));
+        }
+
+        if let Some(seconds) = read("HTTP_CONNECT_TIMEOUT_SECS") {
+            config.connect_timeout = Duration::from_secs(seconds);
+        }
+
+        if let Some(seconds) = read("HTTP_READ_TIMEOUT_SECS") {
+            config.read_timeout = Duration::from_secs(seconds);
+        }
+
+        if let Some(retries) = read("HTTP_MAX_RETRIES") {
+            config.max_retries = retries as u32;
+        }
+
+        // NaN or infinity would break the token bucket's wait times
+        if let Some(requests_per_second) = std::env::var("HTTP_REQUESTS_PER_SECOND")
+            .ok()
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|requests_per_second| requests_per_second.is_finite())
+        {
+            config.requests_per_second = requests_per_second;
+        }
+
+        if let Some(burst) = read("HTTP_BURST") {
+            config.burst = burst as u32;
+        }
+
+        if let Some(max_concurrent_requests) = read("HTTP_MAX_CONCURRENT_REQUESTS") {
+            config.max_concurrent_requests = max_concurrent_requests as usize;
+        }
+
+        config
+    }
+}
+
+/// Says who's crawling, with a way to reach the bot's operator when one is given.
+pub fn build_user_agent(contact: Option<&str>) -> String {
+    let name = format!("backloggd-discord/{}", env!("CARGO_PKG_VERSION"));
+
+    match contact.map(str::trim).filter(|contact| !contact.is_empty()) {
+        Some(contact) => format!(
+            "{} (Discord bot posting Backloggd reviews; {})",
+            name, contact
+        ),
+        None => format!("{} (Discord bot posting Backloggd reviews)", name),
+    }
+}
+
+pub fn build_client(config: &HttpConfig) -> Result<Client, Error> {
+    Ok(Client::builder()
+        .user_agent(&config.user_agent)
+        .connect_timeout(config.connect_timeout)
+        .read_timeout(config.read_timeout)
+        .build()?)
+}
+
+// Slower rates are raised to this, since waits computed from them overflow a Duration
+const MIN_REQUESTS_PER_SECOND: f64 = 1.0 / 3600.0;
+
+/// Refills at a steady rate up to its capacity, each request taking a token.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_second: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_second: f64, now: Instant) -> Self {
+        let capacity = capacity.max(1) as f64;
+
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_second: refill_per_second.max(MIN_REQUESTS_PER_SECOND),
+            last_refill: now,
+        }
+    }
+
+    /// Takes a token and returns how long to wait before using it. Tokens can go below zero, so
+    /// requests waiting on the bucket are spaced out rather than all going at once.
+    pub fn take(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.refill_per_second).min(self.capacity);
+        self.last_refill = self.last_refill.max(now);
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs_f64(-self.tokens / self.refill_per_second)
+    }
+}
+
+/// Rate limits requests per host and caps how many are in flight at once. It's shared by every
+/// clone of the scraper, so commands and the publisher draw from the same limits.
+#[derive(Debug)]
+pub struct Throttle {
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    permits: Arc<Semaphore>,
+    requests_per_second: f64,
+    burst: u32,
+}
+
+impl Throttle {
+    pub fn new(config: &HttpConfig) -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+            permits: Arc::new(Semaphore::new(config.max_concurrent_requests.max(1))),
+            requests_per_second: config.requests_per_second,
+            burst: config.burst,
+        }
+    }
+
+    /// Waits for a free slot and then for the host's rate limit. The request is in flight until
+    /// the returned permit is dropped.
+    #[instrument(skip(self), fields(queued_ms, throttled_ms))]
+    pub async fn acquire(&self, host: &str) -> Option<OwnedSemaphorePermit> {
+        let queued = Instant::now();
+        // The semaphore is never closed, so this only fails if that changes
+        let permit = self.permits.clone().acquire_owned().await.ok();
+        tracing::Span::current().record("queued_ms", queued.elapsed().as_millis() as u64);
+
+        if self.requests_per_second <= 0.0 {
+            return permit;
+        }
+
+        let wait = match self.buckets.lock() {
+            Ok(mut buckets) => {
+                let now = Instant::now();
+                buckets
+                    .entry(host.to_string())
+                    .or_insert_with(|| TokenBucket::new(self.burst, self.requests_per_second, now))
+                    .take(now)
+            }
+            Err(_) => Duration::ZERO,
+        };
+
+        tracing::Span::current().record("throttled_ms", wait.as_millis() as u64);
+        tokio::time::sleep(wait).await;
+
+        permit
+    }
+}
+
+/// A response that keeps its slot in the throttle until its body has been read, since reading
+/// the body is still talking to the host.
+#[derive(Debug)]
+pub struct ThrottledResponse {
+    response: Response,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl ThrottledResponse {
+    pub async fn text(self) -> Result<String, reqwest::Error> {
+        self.response.text().await
+    }
+}
+
+impl Deref for ThrottledResponse {
+    type Target = Response;
+
+    fn deref(&self) -> &Response {
+        &self.response
+    }
+}
+
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Timeouts, refused connections and resets are worth retrying, unlike errors building the
+/// request or following redirects.
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+/// Returns how long to wait before the given retry, starting at 0. The delay doubles each retry
+/// up to the max, and `jitter` from 0 to 1 picks a point in its upper half so clients that failed
+/// together don't retry together.
+pub fn backoff_delay(retry: u32, config: &HttpConfig, jitter: f64) -> Duration {
+    let exponential = config
+        .initial_backoff
+        .saturating_mul(2_u32.saturating_pow(retry))
+        .min(config.max_backoff);
+
+    exponential.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+}
+
+/// Reads a Retry-After header, which is either a number of seconds or an HTTP date.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+
+    // A date in the past means it's fine to retry now
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Sends the request through the throttle, retrying GET and HEAD requests that fail with a 429,
+/// a 5xx or a network error. Once the retries run out the last response or error is returned as
+/// it is.
+pub async fn send_with_retry(
+    request: RequestBuilder,
+    config: &HttpConfig,
+    throttle: &Throttle,
+) -> Result<ThrottledResponse, reqwest::Error> {
+    let built = request.try_clone().and_then(|request| request.build().ok());
+
+    let idempotent = built
+        .as_ref()
+        .is_some_and(|request| matches!(*request.method(), Method::GET | Method::HEAD));
+
+    let host = built
+        .as_ref()
+        .and_then(|request| request.url().host_str().map(str::to_string))
+        .unwrap_or_default();
+
+    let mut retry = 0;
+
+    loop {
+        let attempt = match request.try_clone() {
+            Some(attempt) if idempotent && retry < config.max_retries => attempt,
+            // Last attempt, or a request that can't be safely sent twice
+            _ => {
+                let permit = throttle.acquire(&host).await;
+                return request.send().await.map(|response| ThrottledResponse {
+                    response,
+                    _permit: permit,
+                });
+            }
+        };
+
+        let jitter = rand::random::<f64>();
+        let backoff = backoff_delay(retry, config, jitter);
+
+        // A response that's retried gives its permit back before backing off, so a retry doesn't
+        // hold up other requests
+        let permit = throttle.acquire(&host).await;
+        let result = attempt.send().await.map(|response| ThrottledResponse {
+            response,
+            _permit: permit,
+        });
+
+        let delay = match result {
+            Ok(response) if is_retryable_status(response.status()) => {
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| parse_retry_after(value, Utc::now()));
+
+                match retry_after {
+                    // Waiting longer would hold up the feed loop, so leave it to the next poll
+                    Some(retry_after) if retry_after > config.max_backoff => return Ok(response),
+                    Some(retry_after) => retry_after,
+                    None => backoff,
+                }
+            }
+            Err(error) if is_retryable_error(&error) => {
+                warn!({ url = ?error.url(), error = ?error, retry = retry }, "Retrying failed request");
+                backoff
+            }
+            result => return result,
+        };
+
+        tokio::time::sleep(delay).await;
+        retry += 1;
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    pub(crate) fn build_config() -> HttpConfig {
+        HttpConfig {
+            user_agent: build_user_agent(Some("ops@example.com")),
+            connect_timeout: Duration::from_secs(1),
+            read_timeout: Duration::from_millis(200),
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(10),
+            requests_per_second: 0.0,
+            burst: 1,
+            max_concurrent_requests: 4,
+        }
+    }
+
+    async fn send(request: RequestBuilder, config: &HttpConfig) -> ThrottledResponse {
+        send_with_retry(request, config, &Throttle::new(config))
+            .await
+            .unwrap()
+    }
+
+    /// Serves one scripted response per connection, repeating the last one once the script runs
+    /// out. None closes the connection without responding. Returns the server's URL and the
+    /// requests it got.
+    pub(crate) async fn start_stub_server(
+        script: Vec<Option<&'static str>>,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+
+                let mut buffer = [0; 1024];
+                let length = stream.read(&mut buffer).await.unwrap_or(0);
+
+                let index = {
+                    let mut received = received.lock().unwrap();
+                    received.push(String::from_utf8_lossy(&buffer[..length]).to_string());
+                    received.len() - 1
+                };
+                let response = script[index.min(script.len() - 1)];
+
+                if let Some(response) = response {
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+
+                let _ = stream.shutdown().await;
+            }
+        });
+
+        (url, requests)
+    }
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+    const NOT_FOUND: &str =
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const UNAVAILABLE: &str =
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const TOO_MANY_REQUESTS: &str = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const TOO_MANY_REQUESTS_LATER: &str = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 3600\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+    #[test]
+    fn backoff_delay_doubles_up_to_max() {
+        let config = HttpConfig {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+            ..HttpConfig::default()
+        };
+
+        assert_eq!(backoff_delay(0, &config, 1.0), Duration::from_millis(100));
+        assert_eq!(backoff_delay(2, &config, 1.0), Duration::from_millis(400));
+        assert_eq!(backoff_delay(2, &config, 0.0), Duration::from_millis(200));
+        assert_eq!(backoff_delay(10, &config, 1.0), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(40, &config, 1.0), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn parse_retry_after_reads_seconds_and_dates() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:29:30 GMT", now),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_retries_server_errors() {
+        let (url, requests) =
+            start_stub_server(vec![Some(UNAVAILABLE), Some(UNAVAILABLE), Some(OK)]).await;
+        let config = build_config();
+        let client = build_client(&config).unwrap();
+
+        let response = send(client.get(&url), &config).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_returns_last_response_when_retries_run_out() {
+        let (url, requests) = start_stub_server(vec![Some(UNAVAILABLE)]).await;
+        let config = build_config();
+        let client = build_client(&config).unwrap();
+
+        let response = send(client.get(&url), &config).await;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_does_not_retry_client_errors() {
+        let (url, requests) = start_stub_server(vec![Some(NOT_FOUND), Some(OK)]).await;
+        let config = build_config();
+        let client = build_client(&config).unwrap();
+
+        let response = send(client.get(&url), &config).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_does_not_retry_posts() {
+        let (url, requests) = start_stub_server(vec![Some(UNAVAILABLE), Some(OK)]).await;
+        let config = build_config();
+        let client = build_client(&config).unwrap();
+
+        let response = send(client.post(&url), &config).await;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_honors_retry_after() {
+        let (url, requests) = start_stub_server(vec![Some(TOO_MANY_REQUESTS), Some(OK)]).await;
+        let config = build_config();
+        let client = build_client(&config).unwrap();
+
+        let response = send(client.head(&url), &config).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_gives_up_on_long_retry_after() {
+        let (url, requests) =
+            start_stub_server(vec![Some(TOO_MANY_REQUESTS_LATER), Some(OK)]).await;
+        let config = build_config();
+        let client = build_client(&config).unwrap();
+
+        let response = send(client.get(&url), &config).await;
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_retries_dropped_connections() {
+        let (url, requests) = start_stub_server(vec![None, Some(OK)]).await;
+        let config = build_config();
+        let client = build_client(&config).unwrap();
+
+        let response = send(client.get(&url), &config).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn build_user_agent_adds_contact() {
+        let version = env!("CARGO_PKG_VERSION");
+
+        assert_eq!(
+            build_user_agent(Some(" ops@example.com ")),
+            format!(
+                "backloggd-discord/{} (Discord bot posting Backloggd reviews; ops@example.com)",
+                version
+            )
+        );
+        assert_eq!(
+            build_user_agent(Some("")),
+            format!(
+                "backloggd-discord/{} (Discord bot posting Backloggd reviews)",
+                version
+            )
+        );
+    }
+
+    #[test]
+    fn token_bucket_spaces_out_requests_after_burst() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 4.0, start);
+
+        assert_eq!(bucket.take(start), Duration::ZERO);
+        assert_eq!(bucket.take(start), Duration::ZERO);
+        assert_eq!(bucket.take(start), Duration::from_millis(250));
+        assert_eq!(bucket.take(start), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn token_bucket_refills_up_to_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 4.0, start);
+
+        bucket.take(start);
+        bucket.take(start);
+
+        assert_eq!(
+            bucket.take(start + Duration::from_millis(250)),
+            Duration::ZERO
+        );
+
+        let later = start + Duration::from_secs(60);
+        assert_eq!(bucket.take(later), Duration::ZERO);
+        assert_eq!(bucket.take(later), Duration::ZERO);
+        assert_eq!(bucket.take(later), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn token_bucket_clamps_tiny_rates() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(1, 1e-20, start);
+
+        bucket.take(start);
+
+        assert_eq!(bucket.take(start), Duration::from_secs(3600));
+    }
+
+    #[tokio::test]
+    async fn throttle_caps_requests_in_flight() {
+        let config = HttpConfig {
+            max_concurrent_requests: 1,
+            ..build_config()
+        };
+        let throttle = Throttle::new(&config);
+
+        let permit = throttle.acquire("backloggd.com").await;
+        let blocked =
+            tokio::time::timeout(Duration::from_millis(50), throttle.acquire("backloggd.com"))
+                .await;
+
+        assert!(blocked.is_err());
+
+        drop(permit);
+        assert!(throttle.acquire("backloggd.com").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_rate_limits_each_host() {
+        let (url, requests) = start_stub_server(vec![Some(OK)]).await;
+        let config = HttpConfig {
+            requests_per_second: 20.0,
+            ..build_config()
+        };
+        let client = build_client(&config).unwrap();
+        let throttle = Throttle::new(&config);
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            send_with_retry(client.get(&url), &config, &throttle)
+                .await
+                .unwrap();
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_holds_permit_until_body_is_read() {
+        let (url, _) = start_stub_server(vec![Some(OK)]).await;
+        let config = HttpConfig {
+            max_concurrent_requests: 1,
+            ..build_config()
+        };
+        let client = build_client(&config).unwrap();
+        let throttle = Throttle::new(&config);
+
+        let response = send_with_retry(client.get(&url), &config, &throttle)
+            .await
+            .unwrap();
+
+        assert_eq!(throttle.permits.available_permits(), 0);
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert_eq!(throttle.permits.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_sends_user_agent() {
+        let (url, requests) = start_stub_server(vec![Some(OK)]).await;
+        let config = build_config();
+        let client = build_client(&config).unwrap();
+
+        send(client.get(&url), &config).await;
+
+        assert!(requests.lock().unwrap()[0]
+            .to_lowercase()
+            .contains(&format!("user-agent: {}", config.user_agent).to_lowercase()));
+    }
+}
@@ -0,0 +1,67 @@
+use super::models::LinkedAccount;
+
+const VERIFICATION_CODE_PREFIX: &str = "bld-";
+
+/// Returns a one-time code for the user to put in their Backloggd bio, e.g. bld-3f9a21c4.
+pub fn generate_verification_code() -> String {
+    format!("{}{:08x}", VERIFICATION_CODE_PREFIX, rand::random::<u32>())
+}
+
+/// Backloggd may change the case of the bio, so the code is matched case-insensitively.
+pub fn bio_contains_code(bio: Option<&str>, code: &str) -> bool {
+    match bio {
+        Some(bio) => bio.to_lowercase().contains(&code.to_lowercase()),
+        None => false,
+    }
+}
+
+/// Accounts waiting on verification aren't used, otherwise anyone could claim any username.
+pub fn verified_username(account: Option<LinkedAccount>) -> Option<String> {
+    account
+        .filter(|account| account.verified)
+        .map(|account| account.username)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_account(verified: bool) -> LinkedAccount {
+        LinkedAccount {
+            user_id: 1,
+            username: "username1".to_string(),
+            verification_code: "bld-0000beef".to_string(),
+            verified,
+        }
+    }
+
+    #[test]
+    fn generate_verification_code_has_prefix_and_hex_suffix() {
+        let actual = generate_verification_code();
+
+        assert!(actual.starts_with(VERIFICATION_CODE_PREFIX));
+        assert_eq!(actual.len(), VERIFICATION_CODE_PREFIX.len() + 8);
+        assert!(actual[VERIFICATION_CODE_PREFIX.len()..]
+            .chars()
+            .all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn bio_contains_code_ignores_case() {
+        let bio = Some("Mostly metroidvanias. BLD-0000BEEF");
+
+        assert!(bio_contains_code(bio, "bld-0000beef"));
+        assert!(!bio_contains_code(bio, "bld-0000cafe"));
+        assert!(!bio_contains_code(None, "bld-0000beef"));
+    }
+
+    #[test]
+    fn verified_username_ignores_unverified_accounts() {
+        assert_eq!(
+            verified_username(Some(build_account(true))).as_deref(),
+            Some("username1")
+        );
+        assert_eq!(verified_username(Some(build_account(false))), None);
+        assert_eq!(verified_username(None), None);
+    }
+}
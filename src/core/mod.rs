@@ -1,8 +1,23 @@
+pub mod backfill;
+pub mod cache;
+pub mod cipher;
+pub mod compare;
 pub mod config;
 pub mod converter;
+pub mod deletion;
+pub mod digest;
+pub mod embed;
+pub mod http;
+pub mod link;
 pub mod models;
 pub mod parser;
 pub mod publisher;
+pub mod recap;
+pub mod recommend;
 pub mod repository;
+pub mod review;
 pub mod scraper;
+pub mod search;
+pub mod stats;
+pub mod unfurl;
 pub mod validator;
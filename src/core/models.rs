@@ -1,14 +1,253 @@
-use chrono::NaiveDateTime;
+use anyhow::anyhow;
+use chrono::{NaiveDateTime, NaiveTime, Weekday};
+use chrono_tz::Tz;
 
 pub struct RssFeed {
     pub id: i64,
     pub url: String,
     pub last_checked: NaiveDateTime,
-    pub etag: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
 }
 
 pub struct Subscription {
     pub id: i64,
     pub rss_feed_id: i64,
     pub channel_id: u64,
+    pub mention: Option<Mention>,
+    pub create_threads: bool,
+    pub thread_archive_minutes: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mention {
+    Role(u64),
+    User(u64),
+    Here,
+}
+
+impl Mention {
+    pub fn to_storage_string(&self) -> String {
+        match self {
+            Mention::Role(id) => format!("role:{}", id),
+            Mention::User(id) => format!("user:{}", id),
+            Mention::Here => "here".to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for Mention {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "here" {
+            return Ok(Mention::Here);
+        }
+
+        match value.split_once(':') {
+            Some(("role", id)) => Ok(Mention::Role(id.parse()?)),
+            Some(("user", id)) => Ok(Mention::User(id.parse()?)),
+            _ => Err(anyhow!("Unknown mention {}", value)),
+        }
+    }
+}
+
+/// A review message posted to a channel, kept so it can be edited when the review changes.
+pub struct DeliveredReview {
+    pub id: i64,
+    pub guid: String,
+    pub rss_feed_id: i64,
+    pub channel_id: u64,
+    pub message_id: u64,
+    pub via_webhook: bool,
+    pub link: String,
+    pub pub_date: NaiveDateTime,
+    pub footer: String,
+    pub description: String,
+    // Consecutive polls where the review was expected in the feed but wasn't there
+    pub missing_polls: i64,
+}
+
+pub struct GuildSettings {
+    pub guild_id: u64,
+    pub deleted_review_action: DeletedReviewAction,
+    // Link previews need the message content intent, so they're opt-in per guild
+    pub unfurl_links: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeletedReviewAction {
+    Delete,
+    Mark,
+}
+
+impl DeletedReviewAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeletedReviewAction::Delete => "delete",
+            DeletedReviewAction::Mark => "mark",
+        }
+    }
+}
+
+impl std::str::FromStr for DeletedReviewAction {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "delete" => Ok(DeletedReviewAction::Delete),
+            "mark" => Ok(DeletedReviewAction::Mark),
+            _ => Err(anyhow!("Unknown deleted review action {}", value)),
+        }
+    }
+}
+
+/// A review seen in a polled feed. Reviews are kept after they leave the feed, so stats, search
+/// and re-posting don't need to fetch them from Backloggd again.
+pub struct StoredReview {
+    pub guid: String,
+    pub rss_feed_id: i64,
+    pub title: String,
+    pub link: String,
+    pub reviewer: String,
+    pub user_rating: i8,
+    pub pub_date: NaiveDateTime,
+    pub description: String,
+    pub image_url: String,
+    // Scraped from the review page, None until it's been scraped
+    pub likes: Option<i64>,
+    pub comments: Option<i64>,
+    pub status: Option<String>,
+}
+
+/// Filters for finding stored reviews, each one that's Some narrows the results.
+#[derive(Debug, Default)]
+pub struct ReviewQuery {
+    pub feed_ids: Option<Vec<i64>>,
+    pub reviewer: Option<String>,
+    // Matches any part of the title, ignoring case
+    pub game: Option<String>,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+    pub min_rating: Option<i8>,
+    pub max_rating: Option<i8>,
+    pub limit: Option<u32>,
+}
+
+/// A full text search over stored reviews' titles and descriptions, limited to the given feeds.
+#[derive(Debug, Default)]
+pub struct ReviewSearch {
+    // Already in FTS5 query syntax
+    pub fts_query: String,
+    pub feed_ids: Vec<i64>,
+    pub reviewer: Option<String>,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+    pub min_rating: Option<i8>,
+    pub max_rating: Option<i8>,
+    pub limit: u32,
+}
+
+pub struct ReviewSearchResult {
+    pub review: StoredReview,
+    // Part of the description around the matched words, which are wrapped in ** for Discord
+    pub snippet: String,
+}
+
+/// A channel that gets a recap of its subscriptions' reviews at the end of each month and year.
+pub struct RecapChannel {
+    pub channel_id: u64,
+    // Start of the month the last recap covered
+    pub last_recap: NaiveDateTime,
+}
+
+/// A Discord user's Backloggd account. Commands only use it once the user has proven they own
+/// the account by putting the verification code in their Backloggd bio.
+pub struct LinkedAccount {
+    pub user_id: u64,
+    pub username: String,
+    pub verification_code: String,
+    pub verified: bool,
+}
+
+/// A scraped value kept so the same page isn't fetched again until it expires.
+pub struct CacheEntry {
+    pub value: String,
+    pub expires_at: NaiveDateTime,
+}
+
+pub struct ChannelWebhook {
+    pub channel_id: u64,
+    // Both are None until the publisher creates the webhook for the first review
+    pub webhook_id: Option<u64>,
+    pub encrypted_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DigestFrequency {
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        }
+    }
+}
+
+impl std::str::FromStr for DigestFrequency {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "daily" => Ok(DigestFrequency::Daily),
+            "weekly" => Ok(DigestFrequency::Weekly),
+            _ => Err(anyhow!("Unknown digest frequency {}", value)),
+        }
+    }
+}
+
+pub struct DigestSchedule {
+    pub channel_id: u64,
+    pub frequency: DigestFrequency,
+    // Only used by weekly digests
+    pub weekday: Option<Weekday>,
+    pub time: NaiveTime,
+    pub timezone: Tz,
+    pub next_run: NaiveDateTime,
+}
+
+pub struct DigestItem {
+    pub id: i64,
+    pub channel_id: u64,
+    pub title: String,
+    pub link: String,
+    pub reviewer: String,
+    pub user_rating: i8,
+    pub pub_date: NaiveDateTime,
+}
+
+/// A review by username1 on 2025-03-01, for tests to override with struct update syntax.
+#[cfg(test)]
+pub fn build_test_review(title: &str, rating: i8) -> StoredReview {
+    StoredReview {
+        guid: format!("{}-{}", title, rating),
+        rss_feed_id: 1,
+        title: title.to_string(),
+        link: "https://backloggd.com/u/username1/review/1/".to_string(),
+        reviewer: "username1".to_string(),
+        user_rating: rating,
+        pub_date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap(),
+        description: "Description".to_string(),
+        image_url: "https://images.igdb.com/igdb/image/1.jpg".to_string(),
+        likes: None,
+        comments: None,
+        status: None,
+    }
 }
@@ -13,6 +13,8 @@ pub struct RssChannel {
     pub title: String,
     pub description: String,
     pub link: String,
+    // Users without reviews have a feed with no items
+    #[serde(default)]
     pub item: Vec<RssItem>,
 }
 
@@ -49,6 +51,18 @@ pub fn parse_rss_xml(rss_xml: &str) -> Result<Rss, Error> {
     return Ok(document);
 }
 
+/// Pages that aren't feeds, or feeds from other sites, mean the user doesn't exist even when the
+/// server answers with a 200.
+pub fn is_backloggd_feed(content: &str) -> bool {
+    match parse_rss_xml(content) {
+        Ok(rss) => reqwest::Url::parse(&rss.channel.link).is_ok_and(|url| {
+            url.host_str()
+                .is_some_and(|host| host == "backloggd.com" || host.ends_with(".backloggd.com"))
+        }),
+        Err(_) => false,
+    }
+}
+
 mod backloggd_date_format {
     use chrono::NaiveDateTime;
     use serde::{self, Deserialize, Deserializer, Serializer};
@@ -67,8 +81,28 @@ mod backloggd_date_format {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let dt = converter::parse_backloggd_rss_date(&s).unwrap();
-        Ok(dt)
+        converter::parse_backloggd_rss_date(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A review by username1 on the given day of January 2025, for tests to override with struct
+/// update syntax.
+#[cfg(test)]
+pub fn build_test_item(guid: &str, day: u32) -> RssItem {
+    RssItem {
+        title: "Item".to_string(),
+        link: "https://backloggd.com/u/username1/review/1/".to_string(),
+        pub_date: chrono::NaiveDate::from_ymd_opt(2025, 1, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap(),
+        description: "Description".to_string(),
+        guid: guid.to_string(),
+        user_rating: 8,
+        reviewer: "username1".to_string(),
+        image: RssImage {
+            url: "https://images.igdb.com/igdb/image/1.jpg".to_string(),
+        },
     }
 }
 
@@ -76,6 +110,10 @@ mod backloggd_date_format {
 mod tests {
     use super::*;
 
+    const FEED_XML: &str = include_str!("fixtures/feed_username1.xml");
+    const FEED_EMPTY_XML: &str = include_str!("fixtures/feed_empty.xml");
+    const USER_HTML: &str = include_str!("fixtures/user.html");
+
     #[test]
     fn parse_rss_xml_returns_rss_channel() {
         let rss_content = r#"<rss version="2.0">
@@ -110,7 +148,7 @@ mod tests {
                 </channel>
             </rss>"#;
 
-        let actual = parse_rss_xml(&rss_content);
+        let actual = parse_rss_xml(rss_content);
 
         match actual {
             Ok(value) => {
@@ -137,4 +175,34 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn is_backloggd_feed_accepts_feeds() {
+        assert!(is_backloggd_feed(FEED_XML));
+        assert!(is_backloggd_feed(FEED_EMPTY_XML));
+    }
+
+    #[test]
+    fn is_backloggd_feed_rejects_other_content() {
+        let other_feed = r#"<rss version="2.0">
+                <channel>
+                    <title>Example</title>
+                    <description>Example</description>
+                    <link>https://example.com/</link>
+                </channel>
+            </rss>"#;
+
+        let lookalike_feed = r#"<rss version="2.0">
+                <channel>
+                    <title>Example</title>
+                    <description>Example</description>
+                    <link>https://example.com/backloggd.com/</link>
+                </channel>
+            </rss>"#;
+
+        assert!(!is_backloggd_feed(other_feed));
+        assert!(!is_backloggd_feed(lookalike_feed));
+        assert!(!is_backloggd_feed(USER_HTML));
+        assert!(!is_backloggd_feed(""));
+    }
 }
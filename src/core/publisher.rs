@@ -1,20 +1,37 @@
+use super::backfill;
+use super::cipher::SecretCipher;
 use super::converter;
-use super::models::RssFeed;
-use super::scraper::ReviewMetadata;
+use super::deletion;
+use super::digest;
+use super::embed;
+use super::models::{
+    DeletedReviewAction, DeliveredReview, DigestSchedule, Mention, RssFeed, Subscription,
+};
+use super::recap::{self, RecapPeriod};
 use super::{
-    parser::{self, RssChannel, RssItem},
+    parser::{self, RssItem},
     repository::Repository,
-    scraper::{RssRequest, Scraper},
+    scraper::{self, ReviewMetadata, RssRequest, Scraper},
 };
+use anyhow::anyhow;
 use anyhow::Error;
-use poise::serenity_prelude::Http;
-use poise::serenity_prelude::{Color, CreateEmbed};
+use poise::serenity_prelude::{
+    AutoArchiveDuration, Builder, Channel, ChannelId, ChannelType, Color, CreateAllowedMentions,
+    CreateEmbed, CreateEmbedFooter, CreateForumPost, CreateMessage, CreateThread, CreateWebhook,
+    EditMessage, EditWebhookMessage, ExecuteWebhook, ForumTag, Http, HttpError, Message, MessageId,
+    WebhookId,
+};
+use secrecy::ExposeSecret;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::select;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, instrument};
 
+// Reviews older than this stop having their likes, comments and description kept up to date
+const REFRESH_WINDOW_DAYS: i64 = 7;
+
 pub struct Publisher<S, R>
 where
     S: Scraper,
@@ -23,25 +40,55 @@ where
     scraper: S,
     repository: R,
     ctx: Arc<Http>,
+    // Webhook delivery is disabled when no key is configured to encrypt webhook tokens with
+    cipher: Option<SecretCipher>,
+}
+
+/// How reviews are delivered to a subscribed channel, looked up once per feed.
+struct ChannelDelivery {
+    digest: bool,
+    webhook: bool,
+    // Some when the channel is a forum, with the tags that can be applied to posts
+    forum_tags: Option<Vec<ForumTag>>,
+    mention: Option<Mention>,
+    notify_user_ids: Vec<u64>,
 }
 
 impl<S: Scraper, R: Repository> Publisher<S, R> {
-    pub fn new(scraper: S, repository: R, ctx: Arc<Http>) -> Self {
+    pub fn new(scraper: S, repository: R, ctx: Arc<Http>, cipher: Option<SecretCipher>) -> Self {
         return Self {
             scraper,
             repository,
             ctx,
+            cipher,
         };
     }
 
     #[instrument(skip(self))]
     pub async fn event_loop(&self, cancellation_token: CancellationToken) -> Result<(), Error> {
+        tokio::try_join!(
+            self.feed_loop(cancellation_token.clone()),
+            self.digest_loop(cancellation_token.clone()),
+            self.recap_loop(cancellation_token.clone()),
+            self.refresh_loop(cancellation_token)
+        )?;
+
+        return Ok(());
+    }
+
+    async fn feed_loop(&self, cancellation_token: CancellationToken) -> Result<(), Error> {
         while !cancellation_token.is_cancelled() {
             info!("Started publisher");
 
             // TODO: fetch all feeds, space them out evenly over the re-check time. Spawn each on its own
             // tokio thread so one slow feed doesn't block the others?
-            let feeds_option = self.repository.get_next_unpublished_feed(5).await?;
+            let feeds_option = match self.repository.get_next_unpublished_feed(5).await {
+                Ok(feeds_option) => feeds_option,
+                Err(error) => {
+                    error!("Error while getting feeds to process {}", error);
+                    None
+                }
+            };
 
             if let Some(feeds) = feeds_option {
                 for feed in feeds {
@@ -70,136 +117,1021 @@ impl<S: Scraper, R: Repository> Publisher<S, R> {
         return Ok(());
     }
 
+    async fn digest_loop(&self, cancellation_token: CancellationToken) -> Result<(), Error> {
+        while !cancellation_token.is_cancelled() {
+            if let Err(error) = self.publish_due_digests().await {
+                error!("Error while publishing due digests {}", error);
+            }
+
+            select!(
+                _ = cancellation_token.cancelled() => {
+                    info!("digest publisher cancelled");
+                },
+                _ = tokio::time::sleep(Duration::from_secs(60)) => {}
+            );
+        }
+
+        return Ok(());
+    }
+
+    async fn publish_due_digests(&self) -> Result<(), Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let schedules = self
+            .repository
+            .get_due_digest_schedules(&converter::format_sqlite_date(&now))
+            .await?;
+
+        for schedule in schedules {
+            if let Err(error) = self.publish_digest(&schedule).await {
+                error!(
+                    "Error while publishing digest for channel {} {}",
+                    schedule.channel_id, error
+                );
+            }
+
+            // Always move the schedule forward so a deleted channel doesn't get retried every
+            // minute, unpublished items stay queued for the next digest.
+            let Some(next_run) = digest::next_run(&schedule, now) else {
+                error!(
+                    "Unable to schedule next digest for channel {}",
+                    schedule.channel_id
+                );
+                continue;
+            };
+
+            if let Err(error) = self
+                .repository
+                .update_digest_next_run(
+                    &schedule.channel_id,
+                    &converter::format_sqlite_date(&next_run),
+                )
+                .await
+            {
+                error!(
+                    "Error while scheduling next digest for channel {} {}",
+                    schedule.channel_id, error
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn publish_digest(&self, schedule: &DigestSchedule) -> Result<(), Error> {
+        let items = self
+            .repository
+            .get_digest_items(&schedule.channel_id)
+            .await?;
+
+        if items.is_empty() {
+            info!("No new items for digest in channel {}", schedule.channel_id);
+            return Ok(());
+        }
+
+        // Items cut off by the embed limit aren't posted, so they're left for the next digest
+        let shown_ids: Vec<i64> = items[..digest::count_fitting_items(&items)]
+            .iter()
+            .map(|item| item.id)
+            .collect();
+
+        info!(
+            "Publishing digest of {} of {} items to channel {}",
+            shown_ids.len(),
+            items.len(),
+            schedule.channel_id
+        );
+
+        let channel = ChannelId::from(schedule.channel_id);
+        let message = poise::serenity_prelude::CreateMessage::new()
+            .add_embed(digest::build_digest_embed(&schedule.frequency, &items));
+
+        channel.send_message(&self.ctx, message).await?;
+
+        self.repository
+            .delete_digest_items(&schedule.channel_id, &shown_ids)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn recap_loop(&self, cancellation_token: CancellationToken) -> Result<(), Error> {
+        while !cancellation_token.is_cancelled() {
+            if let Err(error) = self.publish_due_recaps().await {
+                error!("Error while publishing recaps {}", error);
+            }
+
+            select!(
+                _ = cancellation_token.cancelled() => {
+                    info!("recap publisher cancelled");
+                },
+                _ = tokio::time::sleep(Duration::from_secs(3600)) => {}
+            );
+        }
+
+        return Ok(());
+    }
+
+    async fn publish_due_recaps(&self) -> Result<(), Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let channels = self.repository.get_recap_channels().await?;
+
+        for channel in channels {
+            let recaps = recap::due_recaps(channel.last_recap, now);
+
+            for period in &recaps {
+                if let Err(error) = self.publish_recap(&channel.channel_id, period).await {
+                    error!(
+                        "Error while publishing recap for channel {} {}",
+                        channel.channel_id, error
+                    );
+                }
+            }
+
+            // Like digests, a failed recap isn't retried so a deleted channel isn't
+            // posted to every hour
+            if recaps.is_empty() {
+                continue;
+            }
+
+            let month = recap::last_completed_month(now);
+
+            if let Err(error) = self
+                .repository
+                .update_recap_channel(
+                    &channel.channel_id,
+                    &converter::format_sqlite_date(&month.start()),
+                )
+                .await
+            {
+                error!(
+                    "Error while scheduling next recap for channel {} {}",
+                    channel.channel_id, error
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn publish_recap(&self, channel_id: &u64, period: &RecapPeriod) -> Result<(), Error> {
+        info!(
+            "Publishing {} recap to channel {}",
+            period.name(),
+            channel_id
+        );
+
+        let message = poise::serenity_prelude::CreateMessage::new()
+            .add_embed(recap::build_channel_recap(&self.repository, channel_id, period).await?);
+
+        ChannelId::from(*channel_id)
+            .send_message(&self.ctx, message)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn refresh_loop(&self, cancellation_token: CancellationToken) -> Result<(), Error> {
+        while !cancellation_token.is_cancelled() {
+            if let Err(error) = self.refresh_recent_reviews(&cancellation_token).await {
+                error!("Error while refreshing recent reviews {}", error);
+            }
+
+            select!(
+                _ = cancellation_token.cancelled() => {
+                    info!("review refresh cancelled");
+                },
+                _ = tokio::time::sleep(Duration::from_secs(6 * 3600)) => {}
+            );
+        }
+
+        return Ok(());
+    }
+
+    /// Re-scrapes likes, comments and play status for recently delivered reviews and edits the
+    /// posted messages when they've changed.
+    async fn refresh_recent_reviews(
+        &self,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), Error> {
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(REFRESH_WINDOW_DAYS);
+        let delivered_reviews = self
+            .repository
+            .get_recent_delivered_reviews(&converter::format_sqlite_date(&since))
+            .await?;
+
+        // The same review can be posted to many channels but only needs scraping once
+        let mut reviews_by_link: HashMap<String, Vec<DeliveredReview>> = HashMap::new();
+        for review in delivered_reviews {
+            reviews_by_link
+                .entry(review.link.clone())
+                .or_default()
+                .push(review);
+        }
+
+        info!("Refreshing {} recent reviews", reviews_by_link.len());
+
+        for (link, reviews) in reviews_by_link {
+            if cancellation_token.is_cancelled() {
+                break;
+            }
+
+            // The cached metadata is what's already posted, so it's always scraped again here. Keep
+            // the last known footer rather than blanking it when the scrape fails.
+            let footer = match self.scraper.refresh_review_metadata(&link).await {
+                Some(metadata) => {
+                    self.save_review_metadata(&link, &metadata).await;
+                    embed::build_footer(Some(metadata))
+                }
+                None => continue,
+            };
+
+            for review in reviews.iter().filter(|review| review.footer != footer) {
+                match self
+                    .edit_delivered_review(review, &footer, &review.description)
+                    .await
+                {
+                    Ok(()) => {
+                        self.repository
+                            .update_delivered_review(&review.id, &footer, &review.description)
+                            .await?;
+                    }
+                    Err(error) => {
+                        error!(
+                            "Error while refreshing review message {} {}",
+                            review.message_id, error
+                        );
+                    }
+                }
+            }
+
+            // This is low priority work, so space out the requests to Backloggd
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+
+        Ok(())
+    }
+
+    /// Edits posted messages whose review text was changed on Backloggd since it was delivered.
+    async fn update_edited_reviews(&self, items: &[RssItem]) -> Result<(), Error> {
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(REFRESH_WINDOW_DAYS);
+
+        for item in items.iter().filter(|item| item.pub_date >= since) {
+            let delivered_reviews = self.repository.get_delivered_reviews(&item.guid).await?;
+
+            for review in delivered_reviews
+                .iter()
+                .filter(|review| review.description != item.description)
+            {
+                match self
+                    .edit_delivered_review(review, &review.footer, &item.description)
+                    .await
+                {
+                    Ok(()) => {
+                        self.repository
+                            .update_delivered_review(&review.id, &review.footer, &item.description)
+                            .await?;
+                    }
+                    Err(error) => {
+                        error!(
+                            "Error while updating edited review message {} {}",
+                            review.message_id, error
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn edit_delivered_review(
+        &self,
+        review: &DeliveredReview,
+        footer: &str,
+        description: &str,
+    ) -> Result<(), Error> {
+        let channel = ChannelId::new(review.channel_id);
+        let message_id = MessageId::new(review.message_id);
+
+        // Start from the posted embed so everything that hasn't changed stays as it was
+        let message = channel.message(&self.ctx, message_id).await?;
+        let embed = message
+            .embeds
+            .into_iter()
+            .next()
+            .ok_or(anyhow!("Review message {} has no embed", review.message_id))?;
+
+        let embed = CreateEmbed::from(embed)
+            .description(converter::truncate_review(description))
+            .footer(CreateEmbedFooter::new(footer));
+
+        if !review.via_webhook {
+            channel
+                .edit_message(&self.ctx, message_id, EditMessage::new().embed(embed))
+                .await?;
+            return Ok(());
+        }
+
+        // Messages sent by a webhook can only be edited by that same webhook
+        let (webhook_id, token) = self.get_webhook_credentials(review.channel_id).await?;
+
+        EditWebhookMessage::new()
+            .embed(embed)
+            .execute(&self.ctx, (webhook_id, &token, message_id))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_webhook_credentials(&self, channel_id: u64) -> Result<(WebhookId, String), Error> {
+        let cipher = self.cipher.as_ref().ok_or(anyhow!(
+            "No webhook_key configured to manage webhook messages"
+        ))?;
+
+        let (webhook_id, encrypted_token) = self
+            .repository
+            .get_webhook_channel(&channel_id)
+            .await?
+            .and_then(|webhook| webhook.webhook_id.zip(webhook.encrypted_token))
+            .ok_or(anyhow!("No webhook stored for channel {}", channel_id))?;
+
+        Ok((
+            WebhookId::new(webhook_id),
+            cipher.decrypt(&encrypted_token)?,
+        ))
+    }
+
+    /// Removes the posts of reviews that have disappeared from the feed once they've been missing
+    /// for long enough, or straight away when their page on Backloggd is gone.
+    async fn remove_deleted_reviews(&self, feed_id: &i64, items: &[RssItem]) -> Result<(), Error> {
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(REFRESH_WINDOW_DAYS);
+        let delivered_reviews = self
+            .repository
+            .get_feed_delivered_reviews(feed_id, &converter::format_sqlite_date(&since))
+            .await?;
+
+        let missing_reviews = deletion::find_missing_reviews(&delivered_reviews, items);
+
+        // Reviews that came back were only missing from an incomplete feed
+        for review in delivered_reviews.iter().filter(|review| {
+            review.missing_polls > 0
+                && !missing_reviews
+                    .iter()
+                    .any(|missing| missing.id == review.id)
+        }) {
+            self.repository
+                .update_delivered_review_missing_polls(&review.id, &0)
+                .await?;
+        }
+
+        // The same review can be posted to many channels but only needs checking once
+        let mut deleted_links: HashMap<&str, bool> = HashMap::new();
+
+        for review in missing_reviews {
+            let missing_polls = review.missing_polls + 1;
+
+            let is_review_deleted = match deleted_links.get(review.link.as_str()) {
+                Some(is_deleted) => *is_deleted,
+                None => {
+                    let is_deleted = self.scraper.is_review_deleted(&review.link).await;
+                    deleted_links.insert(&review.link, is_deleted);
+                    is_deleted
+                }
+            };
+
+            if !deletion::should_remove(missing_polls, is_review_deleted) {
+                self.repository
+                    .update_delivered_review_missing_polls(&review.id, &missing_polls)
+                    .await?;
+                continue;
+            }
+
+            info!(
+                "Review {} was deleted on Backloggd, removing message {}",
+                review.guid, review.message_id
+            );
+
+            if let Err(error) = self.remove_delivered_review(review).await {
+                error!(
+                    "Error while removing deleted review message {} {}",
+                    review.message_id, error
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn remove_delivered_review(&self, review: &DeliveredReview) -> Result<(), Error> {
+        let channel = ChannelId::new(review.channel_id);
+        let message_id = MessageId::new(review.message_id);
+
+        let guild_id = match channel.to_channel(&self.ctx).await? {
+            Channel::Guild(channel) => channel.guild_id.get(),
+            _ => return Err(anyhow!("Channel {} is not in a guild", review.channel_id)),
+        };
+
+        let settings = self.repository.get_guild_settings(&guild_id).await?;
+
+        match settings.deleted_review_action {
+            DeletedReviewAction::Delete => {
+                if review.channel_id == review.message_id {
+                    // Forum posts share their id with their first message, so remove the whole post
+                    channel.delete(&self.ctx).await?;
+                } else if review.via_webhook {
+                    let (webhook_id, token) =
+                        self.get_webhook_credentials(review.channel_id).await?;
+                    self.ctx
+                        .delete_webhook_message(webhook_id, None, &token, message_id)
+                        .await?;
+                } else {
+                    channel.delete_message(&self.ctx, message_id).await?;
+                }
+
+                self.repository.delete_delivered_review(&review.id).await?;
+            }
+            DeletedReviewAction::Mark => {
+                let message = channel.message(&self.ctx, message_id).await?;
+                let embed = message
+                    .embeds
+                    .into_iter()
+                    .next()
+                    .ok_or(anyhow!("Review message {} has no embed", review.message_id))?;
+
+                let embed = CreateEmbed::from(embed)
+                    .description("*This review was deleted on Backloggd.*")
+                    .color(Color::DARK_GREY);
+
+                if review.via_webhook {
+                    let (webhook_id, token) =
+                        self.get_webhook_credentials(review.channel_id).await?;
+                    EditWebhookMessage::new()
+                        .embed(embed)
+                        .execute(&self.ctx, (webhook_id, &token, message_id))
+                        .await?;
+                } else {
+                    channel
+                        .edit_message(&self.ctx, message_id, EditMessage::new().embed(embed))
+                        .await?;
+                }
+
+                self.repository
+                    .mark_delivered_review_removed(&review.id)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Posts the most recent reviews in the feed to a single subscription, for channels that
+    /// would otherwise stay empty until the user writes their next review. Returns the number of
+    /// reviews posted.
+    pub async fn backfill(
+        &self,
+        feed_url: &str,
+        sub: &Subscription,
+        count: usize,
+    ) -> Result<usize, Error> {
+        info!("Backfilling {} reviews from feed {}", count, feed_url);
+        // Always fetch the full feed, the stored validators belong to the publisher's polling
+        let request = RssRequest::new(feed_url);
+
+        let content = self
+            .scraper
+            .get_rss_feed_content(&request)
+            .await?
+            .content
+            .ok_or(anyhow!("No content returned for feed {}", feed_url))?;
+
+        let rss_feed = parser::parse_rss_xml(&content)?;
+
+        // Running a backfill twice shouldn't post the same reviews again
+        let mut delivered_guids = HashSet::new();
+        for item in &rss_feed.channel.item {
+            if self
+                .get_delivered_channel_ids(&item.guid)
+                .await?
+                .contains(&sub.channel_id)
+            {
+                delivered_guids.insert(item.guid.clone());
+            }
+        }
+
+        let items =
+            backfill::select_backfill_items(&rss_feed.channel.item, &delivered_guids, count);
+
+        // Catching up on old reviews shouldn't ping anyone
+        let delivery = ChannelDelivery {
+            mention: None,
+            notify_user_ids: vec![],
+            ..self.get_channel_delivery(&sub.rss_feed_id, sub).await?
+        };
+
+        let profile_pic_url = self
+            .get_profile_pic_url(feed_url, &rss_feed.channel.item)
+            .await;
+
+        for (index, item) in items.iter().enumerate() {
+            // Space out the posts and scrapes so a backfill doesn't hit Discord or Backloggd
+            // in a burst
+            if index > 0 {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+
+            let review_metadata = self.scraper.get_review_metadata(&item.link).await;
+            let footer = embed::build_footer(review_metadata);
+
+            let embed =
+                embed::build_review_embed(&rss_feed.channel, item, &profile_pic_url, &footer);
+
+            self.deliver_review(sub, &delivery, item, &embed, &profile_pic_url, &footer)
+                .await?;
+        }
+
+        Ok(items.len())
+    }
+
     async fn process_feed(&self, feed: RssFeed) -> Result<(), Error> {
         info!("Processing feed {}", feed.url);
         let request = RssRequest {
             url: feed.url,
             etag: feed.etag,
+            last_modified: feed.last_modified,
         };
 
         let rss_response = self.scraper.get_rss_feed_content(&request).await?;
 
-        if let Some(content) = rss_response.content {
-            let etag = match rss_response.etag {
-                Some(value) => value,
-                None => "default".to_string(),
-            };
+        if let Some(content) = &rss_response.content {
+            info!(
+                "Got RSS content from server with etag {:?} and last modified {:?}",
+                rss_response.etag, rss_response.last_modified
+            );
 
-            info!("Got RSS content from server with etag {}", etag);
+            let rss_feed = parser::parse_rss_xml(content)?;
 
-            let rss_feed = parser::parse_rss_xml(&content)?;
+            // Every review in the feed is kept, so leaderboards include reviews from before the
+            // feed was first polled
+            for item in &rss_feed.channel.item {
+                if let Err(error) = self.repository.save_review(&feed.id, item).await {
+                    error!("Error while saving review {} {}", item.guid, error);
+                }
+            }
 
             let fresh_items: Vec<&RssItem> = rss_feed
                 .channel
                 .item
                 .iter()
-                .filter(|item| &item.pub_date > &feed.last_checked)
+                .filter(|item| item.pub_date > feed.last_checked)
                 .collect();
             let subs_option = self.repository.get_subs(feed.id).await?;
 
-            // Using a time-based check we might not get at-least once delivery of all
-            // subscriptions for a given feed entry. Look into storing feed item guid in database
-            // as 'published'?
-            // Create a published relationship between each sub and each item?
-            // Alternatively use the subs list to drive which feeds to we check, and rely on the
-            // etag/time based cache to ensure we don't hit the site more than once per hour per
-            // feed.
-            if let Some(subs) = subs_option {
+            // Discord is only asked about the channels when there's something to post to them
+            if let Some(subs) = subs_option.filter(|_| !fresh_items.is_empty()) {
+                // A deleted or inaccessible channel shouldn't stop the feed's other channels
+                let mut deliveries = HashMap::new();
+                for sub in &subs {
+                    match self.get_channel_delivery(&feed.id, sub).await {
+                        Ok(delivery) => {
+                            deliveries.insert(sub.id, delivery);
+                        }
+                        Err(error) => {
+                            error!(
+                                "Error while looking up delivery for channel {} {}",
+                                sub.channel_id, error
+                            );
+                        }
+                    }
+                }
+
                 let profile_pic_url = self
-                    .scraper
-                    .get_profile_pic_url_or_default(&rss_feed.channel.description)
-                    .await
-                    .unwrap_or("https://backloggd.com/favicon.ico".to_string());
+                    .get_profile_pic_url(&request.url, &rss_feed.channel.item)
+                    .await;
 
                 for item in fresh_items {
+                    // Items are only fresh by date, so a channel that already got the review from
+                    // an earlier poll or a backfill is skipped
+                    let delivered_channel_ids = self.get_delivered_channel_ids(&item.guid).await?;
+
                     let review_metadata = self.scraper.get_review_metadata(&item.link).await;
 
-                    let embed = &self.build_review_embed(
+                    if let Some(metadata) = &review_metadata {
+                        self.save_review_metadata(&item.link, metadata).await;
+                    }
+
+                    let footer = embed::build_footer(review_metadata);
+
+                    let embed = &embed::build_review_embed(
                         &rss_feed.channel,
                         item,
                         &profile_pic_url,
-                        "",
-                        "",
-                        "",
+                        &footer,
                     );
                     for sub in &subs {
-                        let channel = poise::serenity_prelude::ChannelId::from(sub.channel_id);
-                        let message =
-                            poise::serenity_prelude::CreateMessage::new().add_embed(embed.clone());
-
-                        // Probably don't want to error the whole function here, if someone deleted
-                        // a channel it would not allow publishing others who use the feed.
-                        // Should clean up dangling channels.
-                        channel.send_message(&self.ctx, message).await?;
+                        let Some(delivery) = deliveries.get(&sub.id) else {
+                            continue;
+                        };
+
+                        if delivered_channel_ids.contains(&sub.channel_id) {
+                            continue;
+                        }
+
+                        if let Err(error) = self
+                            .deliver_review(sub, delivery, item, embed, &profile_pic_url, &footer)
+                            .await
+                        {
+                            error!(
+                                "Error while delivering review {} to channel {} {}",
+                                item.guid, sub.channel_id, error
+                            );
+                        }
                     }
                 }
             }
 
-            info!("Updating RssFeed {} with Etag {}", feed.id, etag);
-            self.repository
-                .update_feed(&feed.id, &converter::get_sqlite_now(), &etag)
-                .await?;
+            if let Err(error) = self.update_edited_reviews(&rss_feed.channel.item).await {
+                error!("Error while updating edited reviews {}", error);
+            }
+
+            if let Err(error) = self
+                .remove_deleted_reviews(&feed.id, &rss_feed.channel.item)
+                .await
+            {
+                error!("Error while removing deleted reviews {}", error);
+            }
+        } else {
+            info!(
+                "RssFeed {} hasn't changed since it was last checked",
+                feed.id
+            );
         }
 
+        // An unchanged feed still counts as checked, so it goes to the back of the polling queue
+        // and keeps the validators that were carried forward
+        info!("Updating RssFeed {}", feed.id);
+        self.repository
+            .update_feed(
+                &feed.id,
+                &converter::get_sqlite_now(),
+                rss_response.etag.as_deref(),
+                rss_response.last_modified.as_deref(),
+            )
+            .await?;
+
         Ok(())
     }
 
-    // TODO: scrape like and comment count
-    // TODO: scrape game status (shelved, completed, etc.)
-    fn build_review_embed(
+    async fn get_delivered_channel_ids(&self, guid: &str) -> Result<HashSet<u64>, Error> {
+        let delivered_reviews = self.repository.get_delivered_reviews(guid).await?;
+
+        Ok(delivered_reviews
+            .iter()
+            .map(|review| review.channel_id)
+            .collect())
+    }
+
+    /// The stored metadata only feeds stats and search, so failing to save it isn't worth failing
+    /// the caller.
+    async fn save_review_metadata(&self, link: &str, metadata: &ReviewMetadata) {
+        if let Err(error) = self
+            .repository
+            .update_review_metadata(
+                link,
+                metadata.likes.as_deref().and_then(converter::parse_count),
+                metadata.comments.as_deref().and_then(converter::parse_count),
+                metadata.status.as_deref(),
+            )
+            .await
+        {
+            error!("Error while saving review metadata {} {}", link, error);
+        }
+    }
+
+    async fn get_profile_pic_url(&self, feed_url: &str, items: &[RssItem]) -> String {
+        let profile_pic_url = match scraper::find_profile_url(feed_url, items) {
+            Some(profile_url) => {
+                self.scraper
+                    .get_profile_pic_url_or_default(&profile_url)
+                    .await
+            }
+            None => None,
+        };
+
+        profile_pic_url.unwrap_or(embed::DEFAULT_PROFILE_PIC_URL.to_string())
+    }
+
+    async fn get_channel_delivery(
         &self,
-        channel: &RssChannel,
-        rss_item: &RssItem,
-        profile_pic_url: &str,
-        likes_count: &str,
-        comments_count: &str,
-        status: &str,
-    ) -> CreateEmbed {
-        let author = poise::serenity_prelude::CreateEmbedAuthor::new(&rss_item.reviewer)
-            .url(&channel.link)
-            .icon_url(profile_pic_url);
+        feed_id: &i64,
+        sub: &Subscription,
+    ) -> Result<ChannelDelivery, Error> {
+        let digest = self
+            .repository
+            .get_digest_schedule(&sub.channel_id)
+            .await?
+            .is_some();
 
-        let footer = poise::serenity_prelude::CreateEmbedFooter::new(format!(
-            "{} •  🩷 {}  •  💬 {} ",
-            status, likes_count, comments_count
-        ));
+        let webhook = self.cipher.is_some()
+            && self
+                .repository
+                .get_webhook_channel(&sub.channel_id)
+                .await?
+                .is_some();
 
-        // TODO: truncate a bit more nicely, ending on a word not potentially halfway through one
-        let mut truncated_review = rss_item.description.clone();
-        truncated_review.truncate(1000);
-        truncated_review.push_str("...");
+        let notify_user_ids = self
+            .repository
+            .get_notify_users(feed_id, &sub.channel_id)
+            .await?;
 
-        return poise::serenity_prelude::CreateEmbed::new()
-            .url(&rss_item.link)
-            .color(Color::from_rgb(252, 99, 153))
-            .title(&rss_item.title)
-            .thumbnail(&rss_item.image.url)
-            .description(truncated_review)
-            .footer(footer)
-            .author(author);
+        // Digests don't post individual reviews, so there's no need to ask Discord about the channel
+        let forum_tags = if digest {
+            None
+        } else {
+            match ChannelId::new(sub.channel_id).to_channel(&self.ctx).await? {
+                Channel::Guild(channel) if channel.kind == ChannelType::Forum => {
+                    Some(channel.available_tags)
+                }
+                _ => None,
+            }
+        };
+
+        Ok(ChannelDelivery {
+            digest,
+            webhook,
+            forum_tags,
+            mention: sub.mention,
+            notify_user_ids,
+        })
     }
 
-    fn build_footer(review_metadata: Option<ReviewMetadata>) -> String {
-        if let Some(metadata) = review_metadata {
-            let mut footer = "".to_string();
+    async fn deliver_review(
+        &self,
+        sub: &Subscription,
+        delivery: &ChannelDelivery,
+        item: &RssItem,
+        embed: &CreateEmbed,
+        profile_pic_url: &str,
+        footer: &str,
+    ) -> Result<(), Error> {
+        if delivery.digest {
+            self.repository
+                .save_digest_item(&sub.channel_id, item)
+                .await?;
+            return Ok(());
+        }
+
+        let channel = ChannelId::new(sub.channel_id);
+        let thread_name = converter::format_thread_name(&item.title);
+        let archive_duration = sub.thread_archive_minutes.map(AutoArchiveDuration::from);
+
+        // Forum channels only accept posts, and webhooks can't apply tags, so these are always
+        // posted by the bot.
+        if let Some(forum_tags) = &delivery.forum_tags {
+            let tag_name = converter::rating_to_tag_name(item.user_rating);
+            let message = Self::build_review_message(
+                embed.clone(),
+                delivery.mention.as_ref(),
+                &delivery.notify_user_ids,
+            );
+
+            let mut post = CreateForumPost::new(thread_name, message).set_applied_tags(
+                forum_tags
+                    .iter()
+                    .filter(|tag| tag.name == tag_name)
+                    .map(|tag| tag.id),
+            );
+
+            if let Some(duration) = archive_duration {
+                post = post.auto_archive_duration(duration);
+            }
+
+            // The first message in a forum post shares the post's id
+            let post = channel.create_forum_post(&self.ctx, post).await?;
+            return self
+                .save_delivery(sub, item, post.id.get(), post.id.get(), false, footer)
+                .await;
+        }
+
+        let webhook_message = match (delivery.webhook, &self.cipher) {
+            (true, Some(cipher)) => {
+                let review = Self::build_webhook_review(
+                    embed.clone(),
+                    &item.reviewer,
+                    profile_pic_url,
+                    delivery.mention.as_ref(),
+                    &delivery.notify_user_ids,
+                );
+
+                // A channel the bot can't manage webhooks in still gets the review, just from the bot
+                match self
+                    .send_webhook_review(cipher, sub.channel_id, review)
+                    .await
+                {
+                    Ok(message) => Some(message),
+                    Err(error) => {
+                        error!({ action = "webhook-error", channel_id = sub.channel_id, error = ?error }, "Error posting review through webhook, posting as the bot instead");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let message = match webhook_message {
+            Some(message) => message,
+            None => {
+                let message = Self::build_review_message(
+                    embed.clone(),
+                    delivery.mention.as_ref(),
+                    &delivery.notify_user_ids,
+                );
 
-            if let Some(status) = metadata.status {
-                footer.push_str(&format!("{} • ", status));
+                channel.send_message(&self.ctx, message).await?
             }
+        };
+
+        self.save_delivery(
+            sub,
+            item,
+            sub.channel_id,
+            message.id.get(),
+            message.webhook_id.is_some(),
+            footer,
+        )
+        .await?;
 
-            if let Some(likes) = metadata.likes {
-                footer.push_str(&format!("🩷 {} • ", likes));
+        if sub.create_threads {
+            let mut thread = CreateThread::new(thread_name);
+
+            if let Some(duration) = archive_duration {
+                thread = thread.auto_archive_duration(duration);
             }
 
-            if let Some(comments) = metadata.comments {
-                footer.push_str(&format!("💬 {}", comments));
+            // The review is already posted and saved, so a missing permission only loses the thread
+            if let Err(error) = channel
+                .create_thread_from_message(&self.ctx, message.id, thread)
+                .await
+            {
+                error!(
+                    "Error while creating thread for review {} in channel {} {}",
+                    item.guid, sub.channel_id, error
+                );
             }
+        }
+
+        Ok(())
+    }
+
+    async fn save_delivery(
+        &self,
+        sub: &Subscription,
+        item: &RssItem,
+        channel_id: u64,
+        message_id: u64,
+        via_webhook: bool,
+        footer: &str,
+    ) -> Result<(), Error> {
+        self.repository
+            .save_delivered_review(&DeliveredReview {
+                id: 0,
+                guid: item.guid.clone(),
+                rss_feed_id: sub.rss_feed_id,
+                channel_id,
+                message_id,
+                via_webhook,
+                link: item.link.clone(),
+                pub_date: item.pub_date,
+                footer: footer.to_string(),
+                description: item.description.clone(),
+                missing_polls: 0,
+            })
+            .await
+    }
+
+    async fn send_webhook_review(
+        &self,
+        cipher: &SecretCipher,
+        channel_id: u64,
+        review: ExecuteWebhook,
+    ) -> Result<Message, Error> {
+        let stored_webhook = self
+            .repository
+            .get_webhook_channel(&channel_id)
+            .await?
+            .and_then(|webhook| webhook.webhook_id.zip(webhook.encrypted_token));
+
+        let (webhook_id, token) = match stored_webhook {
+            Some((id, encrypted_token)) => (WebhookId::new(id), cipher.decrypt(&encrypted_token)?),
+            None => self.create_webhook(cipher, channel_id).await?,
+        };
+
+        let message = match review
+            .clone()
+            .execute(&self.ctx, (webhook_id, &token, true))
+            .await
+        {
+            Ok(message) => message,
+            // Someone deleted the webhook from the channel settings, replace it and try again
+            Err(poise::serenity_prelude::Error::Http(HttpError::UnsuccessfulRequest(response)))
+                if response.status_code.as_u16() == 404 =>
+            {
+                info!(
+                    "Webhook for channel {} was deleted, recreating it",
+                    channel_id
+                );
+                let (webhook_id, token) = self.create_webhook(cipher, channel_id).await?;
+                review
+                    .execute(&self.ctx, (webhook_id, &token, true))
+                    .await?
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        // Discord always returns the message when asked to wait for it
+        message.ok_or(anyhow!("Discord didn't return the webhook message"))
+    }
+
+    async fn create_webhook(
+        &self,
+        cipher: &SecretCipher,
+        channel_id: u64,
+    ) -> Result<(WebhookId, String), Error> {
+        let webhook = ChannelId::new(channel_id)
+            .create_webhook(&self.ctx, CreateWebhook::new("Backloggd"))
+            .await?;
+
+        let token = webhook
+            .token
+            .ok_or(anyhow!("Discord didn't return a token for the new webhook"))?
+            .expose_secret()
+            .to_string();
+
+        self.repository
+            .update_channel_webhook(&channel_id, &webhook.id.get(), &cipher.encrypt(&token)?)
+            .await?;
+
+        Ok((webhook.id, token))
+    }
+
+    /// Only the mentions configured for the subscription are allowed to ping anyone.
+    fn build_review_message(
+        embed: CreateEmbed,
+        mention: Option<&Mention>,
+        notify_user_ids: &[u64],
+    ) -> CreateMessage {
+        let message = CreateMessage::new().add_embed(embed);
+
+        let content = converter::format_mentions(mention, notify_user_ids);
+        if content.is_empty() {
+            return message;
+        }
+
+        return message
+            .content(content)
+            .allowed_mentions(Self::build_allowed_mentions(mention, notify_user_ids));
+    }
+
+    /// Posts the review as though the reviewer sent it themselves.
+    fn build_webhook_review(
+        embed: CreateEmbed,
+        reviewer: &str,
+        profile_pic_url: &str,
+        mention: Option<&Mention>,
+        notify_user_ids: &[u64],
+    ) -> ExecuteWebhook {
+        let review = ExecuteWebhook::new()
+            .username(reviewer)
+            .avatar_url(profile_pic_url)
+            .embed(embed);
+
+        let content = converter::format_mentions(mention, notify_user_ids);
+        if content.is_empty() {
+            return review;
+        }
+
+        return review
+            .content(content)
+            .allowed_mentions(Self::build_allowed_mentions(mention, notify_user_ids));
+    }
 
-            // TODO: properly handle separators
+    fn build_allowed_mentions(
+        mention: Option<&Mention>,
+        notify_user_ids: &[u64],
+    ) -> CreateAllowedMentions {
+        let mut role_ids = vec![];
+        let mut user_ids = notify_user_ids.to_vec();
+        let mut here = false;
 
-            return footer;
+        match mention {
+            Some(Mention::Role(id)) => role_ids.push(*id),
+            Some(Mention::User(id)) => user_ids.push(*id),
+            Some(Mention::Here) => here = true,
+            None => {}
         }
 
-        "".to_string()
+        return CreateAllowedMentions::new()
+            .roles(role_ids)
+            .users(user_ids)
+            .everyone(here);
     }
 }
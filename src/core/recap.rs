@@ -0,0 +1,386 @@
+use anyhow::Error;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use poise::serenity_prelude::{Color, CreateEmbed};
+use std::collections::HashMap;
+
+use super::converter;
+use super::models::StoredReview;
+use super::repository::Repository;
+use super::stats;
+
+// Recaps are a summary, so each list is kept shorter than the leaderboard's
+const RECAP_LIST_SIZE: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecapPeriod {
+    Month { year: i32, month: u32 },
+    Year(i32),
+}
+
+impl RecapPeriod {
+    pub fn start(&self) -> NaiveDateTime {
+        let date = match self {
+            RecapPeriod::Month { year, month } => NaiveDate::from_ymd_opt(*year, *month, 1),
+            RecapPeriod::Year(year) => NaiveDate::from_ymd_opt(*year, 1, 1),
+        };
+
+        date.unwrap_or_default().and_time(chrono::NaiveTime::MIN)
+    }
+
+    /// The first moment after the period, so reviews are in it when start <= date < end.
+    pub fn end(&self) -> NaiveDateTime {
+        let date = match self {
+            RecapPeriod::Month { year, month: 12 } => NaiveDate::from_ymd_opt(*year + 1, 1, 1),
+            RecapPeriod::Month { year, month } => NaiveDate::from_ymd_opt(*year, *month + 1, 1),
+            RecapPeriod::Year(year) => NaiveDate::from_ymd_opt(*year + 1, 1, 1),
+        };
+
+        date.unwrap_or_default().and_time(chrono::NaiveTime::MIN)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            RecapPeriod::Month { .. } => self.start().format("%B %Y").to_string(),
+            RecapPeriod::Year(year) => year.to_string(),
+        }
+    }
+}
+
+/// Returns the month before the one `now` is in.
+pub fn last_completed_month(now: NaiveDateTime) -> RecapPeriod {
+    match now.month() {
+        1 => RecapPeriod::Month {
+            year: now.year() - 1,
+            month: 12,
+        },
+        month => RecapPeriod::Month {
+            year: now.year(),
+            month: month - 1,
+        },
+    }
+}
+
+/// Returns the recaps to post when the last one covered the month starting at `last_recap`.
+/// Only the latest month is posted after downtime, rather than a backlog of old recaps, and the
+/// year's recap follows December's.
+pub fn due_recaps(last_recap: NaiveDateTime, now: NaiveDateTime) -> Vec<RecapPeriod> {
+    let month = last_completed_month(now);
+
+    if month.start() <= last_recap {
+        return vec![];
+    }
+
+    match month {
+        RecapPeriod::Month { year, month: 12 } => vec![month, RecapPeriod::Year(year)],
+        _ => vec![month],
+    }
+}
+
+/// Works out which period was asked for, defaulting to the last completed month. A month without
+/// a year is its most recent completed occurrence. Returns None for periods that aren't over yet.
+pub fn resolve_period(
+    year: Option<i32>,
+    month: Option<u32>,
+    now: NaiveDateTime,
+) -> Option<RecapPeriod> {
+    let period = match (year, month) {
+        (None, None) => last_completed_month(now),
+        (Some(year), None) => RecapPeriod::Year(year),
+        (Some(year), Some(month)) => RecapPeriod::Month { year, month },
+        (None, Some(month)) if month < now.month() => RecapPeriod::Month {
+            year: now.year(),
+            month,
+        },
+        (None, Some(month)) => RecapPeriod::Month {
+            year: now.year() - 1,
+            month,
+        },
+    };
+
+    if period.end() > now {
+        return None;
+    }
+
+    Some(period)
+}
+
+/// Returns the games with the highest average rating in stars first, with how many reviews rated
+/// them. Ties go to the game with more ratings.
+pub fn top_rated_games(reviews: &[StoredReview]) -> Vec<(String, f32, usize)> {
+    let mut ratings: HashMap<&str, Vec<i8>> = HashMap::new();
+
+    for review in reviews.iter().filter(|review| review.user_rating > 0) {
+        ratings
+            .entry(review.title.trim())
+            .or_default()
+            .push(review.user_rating);
+    }
+
+    let mut games: Vec<(String, f32, usize)> = ratings
+        .into_iter()
+        .map(|(title, ratings)| {
+            let total: i32 = ratings.iter().map(|rating| *rating as i32).sum();
+            let average = total as f32 / ratings.len() as f32 / 2.0;
+            (title.to_string(), average, ratings.len())
+        })
+        .collect();
+
+    games.sort_by(|a, b| {
+        b.1.total_cmp(&a.1)
+            .then_with(|| b.2.cmp(&a.2))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    games.truncate(RECAP_LIST_SIZE);
+
+    games
+}
+
+/// Returns the game with the most reviews, or None when no game was reviewed more than once.
+pub fn most_reviewed_game(reviews: &[StoredReview]) -> Option<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for review in reviews {
+        *counts.entry(review.title.trim()).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(title, count)| (title.to_string(), count))
+}
+
+/// Builds the recap of reviews in the period from every feed the channel is subscribed to, for
+/// both /recap and the automatic recaps.
+pub async fn build_channel_recap<R: Repository>(
+    repository: &R,
+    channel_id: &u64,
+    period: &RecapPeriod,
+) -> Result<CreateEmbed, Error> {
+    let since = converter::format_sqlite_date(&period.start());
+    let mut reviews = vec![];
+
+    for feed_id in repository.get_channel_feed_ids(&[*channel_id]).await? {
+        reviews.extend(
+            repository
+                .get_feed_reviews(&feed_id, &since)
+                .await?
+                .into_iter()
+                .filter(|review| review.pub_date < period.end()),
+        );
+    }
+
+    Ok(build_recap_embed(period, &reviews))
+}
+
+pub fn build_recap_embed(period: &RecapPeriod, reviews: &[StoredReview]) -> CreateEmbed {
+    let title = match period {
+        RecapPeriod::Month { .. } => format!("{} recap", period.name()),
+        RecapPeriod::Year(_) => format!("{} year in review", period.name()),
+    };
+
+    let embed = CreateEmbed::new()
+        .color(Color::from_rgb(252, 99, 153))
+        .title(title);
+
+    if reviews.is_empty() {
+        return embed.description("No reviews were posted");
+    }
+
+    let reviewers = stats::rank_by_review_count(reviews);
+
+    let mut embed = embed.description(format!(
+        "{} reviews from {} reviewers",
+        reviews.len(),
+        reviewers.len()
+    ));
+
+    let top_rated = top_rated_games(reviews)
+        .iter()
+        .enumerate()
+        .map(|(index, (title, average, count))| {
+            format!(
+                "{}. {} • {} ({})",
+                index + 1,
+                title,
+                converter::format_average_rating(*average),
+                count
+            )
+        })
+        .collect::<Vec<String>>();
+
+    if !top_rated.is_empty() {
+        embed = embed.field("Top rated games", top_rated.join("\n"), false);
+    }
+
+    if let Some((title, count)) = most_reviewed_game(reviews) {
+        embed = embed.field(
+            "Most reviewed game",
+            format!("{} • {} reviews", title, count),
+            false,
+        );
+    }
+
+    if let Some(distribution) =
+        converter::format_rating_distribution(&stats::rating_counts(reviews))
+    {
+        embed = embed.field("Ratings", distribution, false);
+    }
+
+    let most_active = reviewers
+        .iter()
+        .take(RECAP_LIST_SIZE)
+        .enumerate()
+        .map(|(index, (reviewer, count))| format!("{}. {} • {}", index + 1, reviewer, count))
+        .collect::<Vec<String>>();
+
+    embed = embed.field("Most active reviewers", most_active.join("\n"), false);
+
+    if let Some(review) = stats::most_liked_review(reviews) {
+        embed = embed.field(
+            "Most liked review",
+            format!(
+                "[{}]({}) by {} • 🩷 {}",
+                review.title,
+                review.link,
+                review.reviewer,
+                review.likes.unwrap_or(0)
+            ),
+            false,
+        );
+    }
+
+    embed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::build_test_review;
+
+    fn build_date(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn recap_period_end_rolls_over_december() {
+        let period = RecapPeriod::Month {
+            year: 2024,
+            month: 12,
+        };
+
+        assert_eq!(period.start().to_string(), "2024-12-01 00:00:00");
+        assert_eq!(period.end().to_string(), "2025-01-01 00:00:00");
+        assert_eq!(period.name(), "December 2024");
+        assert_eq!(
+            RecapPeriod::Year(2024).end().to_string(),
+            "2025-01-01 00:00:00"
+        );
+    }
+
+    #[test]
+    fn due_recaps_returns_last_month_once() {
+        let now = build_date(2025, 4, 1);
+        let march = RecapPeriod::Month {
+            year: 2025,
+            month: 3,
+        };
+
+        assert_eq!(due_recaps(build_date(2025, 2, 1), now), vec![march]);
+        assert!(due_recaps(march.start(), now).is_empty());
+    }
+
+    #[test]
+    fn due_recaps_adds_year_after_december() {
+        let now = build_date(2025, 1, 1);
+
+        let actual = due_recaps(build_date(2024, 11, 1), now);
+
+        assert_eq!(
+            actual,
+            vec![
+                RecapPeriod::Month {
+                    year: 2024,
+                    month: 12
+                },
+                RecapPeriod::Year(2024)
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_period_picks_most_recent_completed_month() {
+        let now = build_date(2025, 4, 15);
+
+        assert_eq!(
+            resolve_period(None, None, now),
+            Some(RecapPeriod::Month {
+                year: 2025,
+                month: 3
+            })
+        );
+        assert_eq!(
+            resolve_period(None, Some(2), now),
+            Some(RecapPeriod::Month {
+                year: 2025,
+                month: 2
+            })
+        );
+        assert_eq!(
+            resolve_period(None, Some(4), now),
+            Some(RecapPeriod::Month {
+                year: 2024,
+                month: 4
+            })
+        );
+    }
+
+    #[test]
+    fn resolve_period_returns_none_until_period_is_over() {
+        let now = build_date(2025, 4, 15);
+
+        assert_eq!(resolve_period(Some(2025), None, now), None);
+        assert_eq!(resolve_period(Some(2025), Some(4), now), None);
+        assert_eq!(
+            resolve_period(Some(2024), None, now),
+            Some(RecapPeriod::Year(2024))
+        );
+    }
+
+    #[test]
+    fn top_rated_games_orders_by_average_then_count() {
+        let reviews = vec![
+            build_test_review("Celeste", 10),
+            build_test_review("Hades", 10),
+            build_test_review("Hades", 10),
+            build_test_review("Doom", 6),
+            build_test_review("Tetris", 0),
+        ];
+
+        let actual = top_rated_games(&reviews);
+
+        assert_eq!(
+            actual,
+            vec![
+                ("Hades".to_string(), 5.0, 2),
+                ("Celeste".to_string(), 5.0, 1),
+                ("Doom".to_string(), 3.0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn most_reviewed_game_needs_more_than_one_review() {
+        let reviews = vec![
+            build_test_review("Hades", 10),
+            build_test_review("Hades", 8),
+            build_test_review("Celeste", 10),
+        ];
+
+        assert_eq!(most_reviewed_game(&reviews), Some(("Hades".to_string(), 2)));
+        assert_eq!(most_reviewed_game(&reviews[2..]), None);
+    }
+}
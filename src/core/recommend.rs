@@ -0,0 +1,195 @@
+use poise::serenity_prelude::{Color, CreateEmbed, CreateEmbedFooter};
+use std::collections::{HashMap, HashSet};
+
+use super::converter;
+use super::models::StoredReview;
+
+// Four stars, the lowest rating /random and /recommend count as highly rated
+pub const MIN_HIGH_RATING: i8 = 8;
+// Recommendations need agreement, so one enthusiastic reviewer isn't enough
+const MIN_RECOMMENDERS: usize = 2;
+pub const RECOMMENDATION_COUNT: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    pub title: String,
+    pub image_url: String,
+    // Out of 5 stars, across the recommenders' ratings
+    pub average_rating: f32,
+    pub recommenders: Vec<String>,
+}
+
+/// Picks one of the highly rated reviews, `seed` being any random number.
+pub fn pick_highly_rated(reviews: &[StoredReview], seed: u32) -> Option<&StoredReview> {
+    let highly_rated: Vec<&StoredReview> = reviews
+        .iter()
+        .filter(|review| review.user_rating >= MIN_HIGH_RATING)
+        .collect();
+
+    if highly_rated.is_empty() {
+        return None;
+    }
+
+    Some(highly_rated[seed as usize % highly_rated.len()])
+}
+
+/// Returns games several other reviewers rated highly that `username` hasn't reviewed, the ones
+/// with the most recommenders first and ties going to the higher average rating. Titles are
+/// compared ignoring case, since feeds don't link reviews to a game page.
+pub fn recommend_games(reviews: &[StoredReview], username: &str) -> Vec<Recommendation> {
+    let reviewed: HashSet<String> = reviews
+        .iter()
+        .filter(|review| review.reviewer.eq_ignore_ascii_case(username))
+        .map(|review| review.title.trim().to_lowercase())
+        .collect();
+
+    let mut games: HashMap<String, Vec<&StoredReview>> = HashMap::new();
+
+    for review in reviews.iter().filter(|review| {
+        review.user_rating >= MIN_HIGH_RATING && !review.reviewer.eq_ignore_ascii_case(username)
+    }) {
+        let title = review.title.trim().to_lowercase();
+
+        if !reviewed.contains(&title) {
+            games.entry(title).or_default().push(review);
+        }
+    }
+
+    let mut recommendations: Vec<Recommendation> = games
+        .into_values()
+        .filter_map(|reviews| {
+            let mut recommenders: Vec<String> = reviews
+                .iter()
+                .map(|review| review.reviewer.clone())
+                .collect();
+            recommenders.sort();
+            recommenders.dedup();
+
+            if recommenders.len() < MIN_RECOMMENDERS {
+                return None;
+            }
+
+            let total: i32 = reviews.iter().map(|review| review.user_rating as i32).sum();
+            let first = reviews.iter().min_by_key(|review| review.pub_date)?;
+
+            Some(Recommendation {
+                title: first.title.trim().to_string(),
+                image_url: first.image_url.clone(),
+                average_rating: total as f32 / reviews.len() as f32 / 2.0,
+                recommenders,
+            })
+        })
+        .collect();
+
+    recommendations.sort_by(|a, b| {
+        b.recommenders
+            .len()
+            .cmp(&a.recommenders.len())
+            .then_with(|| b.average_rating.total_cmp(&a.average_rating))
+            .then_with(|| a.title.cmp(&b.title))
+    });
+
+    recommendations
+}
+
+pub fn build_recommend_embed(username: &str, recommendations: &[Recommendation]) -> CreateEmbed {
+    let embed = CreateEmbed::new()
+        .color(Color::from_rgb(252, 99, 153))
+        .title(format!("What {} should play next", username));
+
+    let Some(top) = recommendations.first() else {
+        return embed.description(
+            "No games in this server's reviews are rated highly by several reviewers and unplayed by you",
+        );
+    };
+
+    let fields = recommendations.iter().map(|recommendation| {
+        (
+            recommendation.title.clone(),
+            format!(
+                "{} from {}",
+                converter::format_average_rating(recommendation.average_rating),
+                recommendation.recommenders.join(", ")
+            ),
+            false,
+        )
+    });
+
+    embed
+        .thumbnail(&top.image_url)
+        .fields(fields)
+        .footer(CreateEmbedFooter::new(
+            "Based on reviews posted in this server",
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::build_test_review;
+
+    fn build_review(reviewer: &str, title: &str, rating: i8) -> StoredReview {
+        StoredReview {
+            guid: format!("{}-{}", reviewer, title),
+            link: format!("https://backloggd.com/u/{}/review/1/", reviewer),
+            reviewer: reviewer.to_string(),
+            ..build_test_review(title, rating)
+        }
+    }
+
+    #[test]
+    fn pick_highly_rated_skips_low_ratings() {
+        let reviews = vec![
+            build_review("username1", "Doom", 4),
+            build_review("username1", "Hades", 9),
+            build_review("username1", "Tetris", 0),
+            build_review("username1", "Celeste", 10),
+        ];
+
+        let titles: Vec<&str> = (0..4)
+            .filter_map(|seed| pick_highly_rated(&reviews, seed))
+            .map(|review| review.title.as_str())
+            .collect();
+
+        assert_eq!(titles, vec!["Hades", "Celeste", "Hades", "Celeste"]);
+        assert!(pick_highly_rated(&reviews[..1], 0).is_none());
+    }
+
+    #[test]
+    fn recommend_games_needs_several_recommenders() {
+        let reviews = vec![
+            build_review("username1", "Hades", 10),
+            build_review("username2", "HADES", 8),
+            build_review("username1", "Celeste", 10),
+            build_review("username2", "Celeste", 10),
+            build_review("username3", "Celeste", 10),
+            build_review("username1", "Doom", 10),
+            build_review("username2", "Doom", 6),
+        ];
+
+        let actual = recommend_games(&reviews, "caller");
+
+        assert_eq!(
+            actual
+                .iter()
+                .map(|recommendation| (
+                    recommendation.title.as_str(),
+                    recommendation.average_rating
+                ))
+                .collect::<Vec<(&str, f32)>>(),
+            vec![("Celeste", 5.0), ("Hades", 4.5)]
+        );
+        assert_eq!(actual[1].recommenders, vec!["username1", "username2"]);
+    }
+
+    #[test]
+    fn recommend_games_skips_games_the_user_reviewed() {
+        let reviews = vec![
+            build_review("username1", "Hades", 10),
+            build_review("username2", "Hades", 10),
+            build_review("Caller", "hades ", 2),
+        ];
+
+        assert!(recommend_games(&reviews, "caller").is_empty());
+    }
+}
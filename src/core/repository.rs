@@ -1,26 +1,88 @@
 use anyhow::anyhow;
 use anyhow::Error;
 use anyhow::Result;
+use chrono::Weekday;
 use libsql::params;
 use libsql::Builder;
 
 use super::converter;
+use super::models::CacheEntry;
+use super::models::ChannelWebhook;
+use super::models::DeletedReviewAction;
+use super::models::DeliveredReview;
+use super::models::DigestItem;
+use super::models::DigestSchedule;
+use super::models::GuildSettings;
+use super::models::LinkedAccount;
+use super::models::Mention;
+use super::models::RecapChannel;
+use super::models::ReviewQuery;
+use super::models::ReviewSearch;
+use super::models::ReviewSearchResult;
+use super::models::StoredReview;
 use super::models::RssFeed;
 use super::models::Subscription;
+use super::parser::RssItem;
 
 pub trait Repository {
     fn init_database(&self) -> impl std::future::Future<Output = Result<(), Error>>;
     fn save_feed(&self, feed_url: &str) -> impl std::future::Future<Output = Result<i64, Error>>;
-    fn get_feed_id(&self, feed_url: &str) -> impl std::future::Future<Output = Result<i64, Error>>;
-    fn update_feed(&self, id: &i64, last_checked: &str, etag: &str) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_feed_id(&self, feed_url: &str) -> impl std::future::Future<Output = Result<Option<i64>, Error>>;
+    fn update_feed(&self, id: &i64, last_checked: &str, etag: Option<&str>, last_modified: Option<&str>) -> impl std::future::Future<Output = Result<(), Error>>;
     fn delete_feed(&self, id: &i64) -> impl std::future::Future<Output = Result<(), Error>>;
     fn save_sub(&self, id: &i64, channel_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
     fn delete_sub(&self, id: &i64, channel_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
     fn get_channel_feeds(&self, channel_id: &u64) -> impl std::future::Future<Output = Result<Vec<String>, Error>>;
     fn get_next_unpublished_feed(&self, number: i16) -> impl std::future::Future<Output = Result<Option<Vec<RssFeed>>, Error>>;
     fn get_subs(&self, feed_id: i64) -> impl std::future::Future<Output = Result<Option<Vec<Subscription>>, Error>>;
+    fn get_sub(&self, feed_id: &i64, channel_id: &u64) -> impl std::future::Future<Output = Result<Option<Subscription>, Error>>;
+    fn update_sub_mention(&self, feed_id: &i64, channel_id: &u64, mention: Option<&Mention>) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn update_sub_threads(&self, feed_id: &i64, channel_id: &u64, create_threads: bool, archive_minutes: Option<u16>) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn save_notify_user(&self, feed_id: &i64, channel_id: &u64, user_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn delete_notify_user(&self, feed_id: &i64, channel_id: &u64, user_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_notify_users(&self, feed_id: &i64, channel_id: &u64) -> impl std::future::Future<Output = Result<Vec<u64>, Error>>;
+    fn save_delivered_review(&self, review: &DeliveredReview) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_delivered_reviews(&self, guid: &str) -> impl std::future::Future<Output = Result<Vec<DeliveredReview>, Error>>;
+    fn get_recent_delivered_reviews(&self, since: &str) -> impl std::future::Future<Output = Result<Vec<DeliveredReview>, Error>>;
+    fn update_delivered_review(&self, id: &i64, footer: &str, description: &str) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_feed_delivered_reviews(&self, feed_id: &i64, since: &str) -> impl std::future::Future<Output = Result<Vec<DeliveredReview>, Error>>;
+    fn update_delivered_review_missing_polls(&self, id: &i64, missing_polls: &i64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn mark_delivered_review_removed(&self, id: &i64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn delete_delivered_review(&self, id: &i64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_guild_settings(&self, guild_id: &u64) -> impl std::future::Future<Output = Result<GuildSettings, Error>>;
+    fn save_guild_settings(&self, settings: &GuildSettings) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn save_review(&self, feed_id: &i64, item: &RssItem) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn update_review_metadata(&self, link: &str, likes: Option<i64>, comments: Option<i64>, status: Option<&str>) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_feed_reviews(&self, feed_id: &i64, since: &str) -> impl std::future::Future<Output = Result<Vec<StoredReview>, Error>>;
+    fn find_reviews(&self, query: &ReviewQuery) -> impl std::future::Future<Output = Result<Vec<StoredReview>, Error>>;
+    fn search_reviews(&self, search: &ReviewSearch) -> impl std::future::Future<Output = Result<Vec<ReviewSearchResult>, Error>>;
+    fn get_channel_feed_ids(&self, channel_ids: &[u64]) -> impl std::future::Future<Output = Result<Vec<i64>, Error>>;
+    fn save_recap_channel(&self, channel_id: &u64, last_recap: &str) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn delete_recap_channel(&self, channel_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_recap_channels(&self) -> impl std::future::Future<Output = Result<Vec<RecapChannel>, Error>>;
+    fn update_recap_channel(&self, channel_id: &u64, last_recap: &str) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_guild_linked_accounts(&self, guild_id: &u64) -> impl std::future::Future<Output = Result<Vec<LinkedAccount>, Error>>;
+    fn get_linked_account(&self, user_id: &u64) -> impl std::future::Future<Output = Result<Option<LinkedAccount>, Error>>;
+    fn save_linked_account(&self, account: &LinkedAccount) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn delete_linked_account(&self, user_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn save_linked_account_guild(&self, user_id: &u64, guild_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn save_webhook_channel(&self, channel_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn delete_webhook_channel(&self, channel_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_webhook_channel(&self, channel_id: &u64) -> impl std::future::Future<Output = Result<Option<ChannelWebhook>, Error>>;
+    fn update_channel_webhook(&self, channel_id: &u64, webhook_id: &u64, encrypted_token: &str) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn save_digest_schedule(&self, schedule: &DigestSchedule) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn delete_digest_schedule(&self, channel_id: &u64) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_digest_schedule(&self, channel_id: &u64) -> impl std::future::Future<Output = Result<Option<DigestSchedule>, Error>>;
+    fn get_due_digest_schedules(&self, now: &str) -> impl std::future::Future<Output = Result<Vec<DigestSchedule>, Error>>;
+    fn update_digest_next_run(&self, channel_id: &u64, next_run: &str) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn save_digest_item(&self, channel_id: &u64, item: &RssItem) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_digest_items(&self, channel_id: &u64) -> impl std::future::Future<Output = Result<Vec<DigestItem>, Error>>;
+    fn delete_digest_items(&self, channel_id: &u64, ids: &[i64]) -> impl std::future::Future<Output = Result<(), Error>>;
+    fn get_cache_entry(&self, key: &str, now: &str) -> impl std::future::Future<Output = Result<Option<CacheEntry>, Error>>;
+    fn save_cache_entry(&self, key: &str, value: &str, expires_at: &str) -> impl std::future::Future<Output = Result<(), Error>>;
 }
 
+#[derive(Debug, Clone)]
 pub struct SqliteRepository {}
 
 const DATABASE_PATH: &str = "/var/lib/backloggd-discord/db";
@@ -32,7 +94,7 @@ impl Repository for SqliteRepository {
 
         connection
             .execute(
-                "INSERT OR IGNORE INTO RssFeeds (Url, LastChecked, Etag) values (?1, ?2, 'default')",
+                "INSERT OR IGNORE INTO RssFeeds (Url, LastChecked) values (?1, ?2)",
                 params!(feed_url, converter::get_sqlite_now()),
             )
             .await?;
@@ -69,14 +131,20 @@ impl Repository for SqliteRepository {
         }
     }
 
-    async fn update_feed(&self, id: &i64, last_checked: &str, etag: &str) -> Result<(), Error> {
+    async fn update_feed(
+        &self,
+        id: &i64,
+        last_checked: &str,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) -> Result<(), Error> {
         let database = Builder::new_local(DATABASE_PATH).build().await?;
         let connection = database.connect()?;
 
         connection
             .execute(
-                "UPDATE RssFeeds SET LastChecked = (?1), Etag = (?2) WHERE Id = (?3)",
-                params!(last_checked, etag, id),
+                "UPDATE RssFeeds SET LastChecked = (?1), Etag = (?2), LastModified = (?3) WHERE Id = (?4)",
+                params!(last_checked, etag, last_modified, id),
             )
             .await?;
 
@@ -88,7 +156,7 @@ impl Repository for SqliteRepository {
         let connection = database.connect()?;
 
         connection
-            .execute("DELETE FROM RssFeeds WHERE RssFeedId = (?1)", params!(id))
+            .execute("DELETE FROM RssFeeds WHERE Id = (?1)", params!(id))
             .await?;
 
         Ok(())
@@ -119,6 +187,13 @@ impl Repository for SqliteRepository {
             )
             .await?;
 
+        connection
+            .execute(
+                "DELETE FROM NotifyUsers WHERE RssFeedId = (?1) AND ChannelId = (?2)",
+                params!(id, channel_id),
+            )
+            .await?;
+
         Ok(())
     }
 
@@ -165,6 +240,21 @@ impl Repository for SqliteRepository {
             )
             .await?;
 
+        let _ = connection
+            .execute(
+                r#"ALTER TABLE "RssFeeds" ADD COLUMN "LastModified" TEXT"#,
+                params!(),
+            )
+            .await;
+
+        // Feeds used to be saved with a placeholder etag, which was sent to the server as is
+        let _ = connection
+            .execute(
+                "UPDATE RssFeeds SET Etag = NULL WHERE Etag = 'default'",
+                params!(),
+            )
+            .await?;
+
         let _ = connection
             .execute(
                 r#"CREATE TABLE IF NOT EXISTS "Subscriptions" (
@@ -178,6 +268,300 @@ impl Repository for SqliteRepository {
             )
             .await?;
 
+        // SQLite has no ADD COLUMN IF NOT EXISTS, this fails harmlessly once the column exists
+        let _ = connection
+            .execute(
+                r#"ALTER TABLE "Subscriptions" ADD COLUMN "Mention" TEXT"#,
+                params!(),
+            )
+            .await;
+
+        let _ = connection
+            .execute(
+                r#"ALTER TABLE "Subscriptions" ADD COLUMN "CreateThreads" INTEGER NOT NULL DEFAULT 0"#,
+                params!(),
+            )
+            .await;
+
+        let _ = connection
+            .execute(
+                r#"ALTER TABLE "Subscriptions" ADD COLUMN "ThreadArchiveMinutes" INTEGER"#,
+                params!(),
+            )
+            .await;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "NotifyUsers" (
+                        "RssFeedId"	INTEGER NOT NULL,
+                        "ChannelId"	INTEGER NOT NULL,
+                        "UserId"	INTEGER NOT NULL,
+                        PRIMARY KEY("RssFeedId", "ChannelId", "UserId"),
+                        FOREIGN KEY("RssFeedId") REFERENCES "RssFeeds"("Id")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "DeliveredReviews" (
+                        "Id"	INTEGER,
+                        "Guid"	TEXT NOT NULL,
+                        "RssFeedId"	INTEGER NOT NULL,
+                        "ChannelId"	INTEGER NOT NULL,
+                        "MessageId"	INTEGER NOT NULL,
+                        "ViaWebhook"	INTEGER NOT NULL DEFAULT 0,
+                        "Link"	TEXT NOT NULL,
+                        "PubDate"	TEXT NOT NULL,
+                        "Footer"	TEXT NOT NULL,
+                        "Description"	TEXT NOT NULL,
+                        PRIMARY KEY("Id" AUTOINCREMENT),
+                        FOREIGN KEY("RssFeedId") REFERENCES "RssFeeds"("Id")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE INDEX IF NOT EXISTS "DeliveredReviewsGuid" ON "DeliveredReviews" ("Guid");"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"ALTER TABLE "DeliveredReviews" ADD COLUMN "MissingPolls" INTEGER NOT NULL DEFAULT 0"#,
+                params!(),
+            )
+            .await;
+
+        let _ = connection
+            .execute(
+                r#"ALTER TABLE "DeliveredReviews" ADD COLUMN "Removed" INTEGER NOT NULL DEFAULT 0"#,
+                params!(),
+            )
+            .await;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "GuildSettings" (
+                        "GuildId"	INTEGER NOT NULL,
+                        "DeletedReviewAction"	TEXT NOT NULL DEFAULT 'mark',
+                        PRIMARY KEY("GuildId")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"ALTER TABLE "GuildSettings" ADD COLUMN "UnfurlLinks" INTEGER NOT NULL DEFAULT 0"#,
+                params!(),
+            )
+            .await;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "Reviews" (
+                        "Id"	INTEGER,
+                        "Guid"	TEXT NOT NULL UNIQUE,
+                        "RssFeedId"	INTEGER NOT NULL,
+                        "Title"	TEXT NOT NULL,
+                        "Link"	TEXT NOT NULL,
+                        "Reviewer"	TEXT NOT NULL,
+                        "UserRating"	INTEGER NOT NULL,
+                        "PubDate"	TEXT NOT NULL,
+                        "Likes"	INTEGER,
+                        "Description"	TEXT NOT NULL DEFAULT '',
+                        "ImageUrl"	TEXT NOT NULL DEFAULT '',
+                        "Comments"	INTEGER,
+                        "Status"	TEXT,
+                        PRIMARY KEY("Id" AUTOINCREMENT),
+                        FOREIGN KEY("RssFeedId") REFERENCES "RssFeeds"("Id")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE INDEX IF NOT EXISTS "ReviewsRssFeedId" ON "Reviews" ("RssFeedId", "PubDate");"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE INDEX IF NOT EXISTS "ReviewsReviewer" ON "Reviews" ("Reviewer" COLLATE NOCASE);"#,
+                params!(),
+            )
+            .await?;
+
+        let search_table_exists = connection
+            .query(
+                r#"SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'ReviewsSearch'"#,
+                params!(),
+            )
+            .await?
+            .next()
+            .await?
+            .is_some();
+
+        let _ = connection
+            .execute(
+                r#"CREATE VIRTUAL TABLE IF NOT EXISTS "ReviewsSearch" USING fts5(
+                        "Title",
+                        "Description",
+                        content='Reviews',
+                        content_rowid='Id'
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        // Keep the search index in step with the Reviews table it indexes
+        let _ = connection
+            .execute(
+                r#"CREATE TRIGGER IF NOT EXISTS "ReviewsSearchInsert" AFTER INSERT ON "Reviews" BEGIN
+                        INSERT INTO "ReviewsSearch" (rowid, "Title", "Description") VALUES (new."Id", new."Title", new."Description");
+                    END;"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TRIGGER IF NOT EXISTS "ReviewsSearchDelete" AFTER DELETE ON "Reviews" BEGIN
+                        INSERT INTO "ReviewsSearch" ("ReviewsSearch", rowid, "Title", "Description") VALUES ('delete', old."Id", old."Title", old."Description");
+                    END;"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TRIGGER IF NOT EXISTS "ReviewsSearchUpdate" AFTER UPDATE OF "Title", "Description" ON "Reviews" BEGIN
+                        INSERT INTO "ReviewsSearch" ("ReviewsSearch", rowid, "Title", "Description") VALUES ('delete', old."Id", old."Title", old."Description");
+                        INSERT INTO "ReviewsSearch" (rowid, "Title", "Description") VALUES (new."Id", new."Title", new."Description");
+                    END;"#,
+                params!(),
+            )
+            .await?;
+
+        // Indexes reviews stored before the search table existed
+        if !search_table_exists {
+            let _ = connection
+                .execute(
+                    r#"INSERT INTO "ReviewsSearch" ("ReviewsSearch") VALUES ('rebuild');"#,
+                    params!(),
+                )
+                .await?;
+        }
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "RecapChannels" (
+                        "ChannelId"	INTEGER NOT NULL,
+                        "LastRecap"	TEXT NOT NULL,
+                        PRIMARY KEY("ChannelId")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "LinkedAccounts" (
+                        "UserId"	INTEGER NOT NULL,
+                        "Username"	TEXT NOT NULL,
+                        "VerificationCode"	TEXT NOT NULL,
+                        "Verified"	INTEGER NOT NULL DEFAULT 0,
+                        PRIMARY KEY("UserId")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        // The servers each account was linked in, so leaderboards don't have to ask Discord
+        // about every linked member
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "LinkedAccountGuilds" (
+                        "UserId"	INTEGER NOT NULL,
+                        "GuildId"	INTEGER NOT NULL,
+                        PRIMARY KEY("UserId", "GuildId")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "ChannelWebhooks" (
+                        "ChannelId"	INTEGER NOT NULL,
+                        "WebhookId"	INTEGER,
+                        "WebhookToken"	TEXT,
+                        PRIMARY KEY("ChannelId")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "DigestSchedules" (
+                        "ChannelId"	INTEGER NOT NULL,
+                        "Frequency"	TEXT NOT NULL,
+                        "Weekday"	INTEGER,
+                        "Time"	TEXT NOT NULL,
+                        "Timezone"	TEXT NOT NULL,
+                        "NextRun"	TEXT NOT NULL,
+                        PRIMARY KEY("ChannelId")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "DigestItems" (
+                        "Id"	INTEGER,
+                        "ChannelId"	INTEGER NOT NULL,
+                        "Guid"	TEXT NOT NULL,
+                        "Title"	TEXT NOT NULL,
+                        "Link"	TEXT NOT NULL,
+                        "Reviewer"	TEXT NOT NULL,
+                        "UserRating"	INTEGER NOT NULL,
+                        "PubDate"	TEXT NOT NULL,
+                        PRIMARY KEY("Id" AUTOINCREMENT),
+                        UNIQUE("ChannelId", "Guid")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        let _ = connection
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS "ScrapeCache" (
+                        "Key"	TEXT NOT NULL,
+                        "Value"	TEXT NOT NULL,
+                        "ExpiresAt"	TEXT NOT NULL,
+                        PRIMARY KEY("Key")
+                    );"#,
+                params!(),
+            )
+            .await?;
+
+        // Expired entries are never read again, so they're cleared out on start up
+        let _ = connection
+            .execute(
+                "DELETE FROM ScrapeCache WHERE ExpiresAt <= (?1)",
+                params!(converter::get_sqlite_now()),
+            )
+            .await?;
+
         Ok(())
     }
 
@@ -188,7 +572,7 @@ impl Repository for SqliteRepository {
         // Get the identifier of the just inserted URL
         let mut rows = connection
             .query(
-                "SELECT Id, Url, LastChecked, Etag  FROM RssFeeds ORDER BY LastChecked ASC LIMIT (?1)",
+                "SELECT Id, Url, LastChecked, Etag, LastModified FROM RssFeeds ORDER BY LastChecked ASC LIMIT (?1)",
                 params!(number),
             )
             .await?;
@@ -200,14 +584,16 @@ impl Repository for SqliteRepository {
             let id_option = id_value.as_integer();
             let url = row.get_str(1)?;
             let last_checked = converter::parse_sqlite_date(row.get_str(2)?)?;
-            let etag = row.get_str(3)?;
+            let etag = row.get::<Option<String>>(3)?;
+            let last_modified = row.get::<Option<String>>(4)?;
 
             match id_option {
                 Some(id) => rss_feeds.push(RssFeed {
                     id: *id,
                     url: url.to_string(),
                     last_checked,
-                    etag: etag.to_string(),
+                    etag,
+                    last_modified,
                 }),
                 None => {
                     return Err(anyhow!("Unable to parse RssFeeds.Id to integer"));
@@ -215,7 +601,7 @@ impl Repository for SqliteRepository {
             }
         }
 
-        if rss_feeds.len() == 0 {
+        if rss_feeds.is_empty() {
             return Ok(None);
         }
 
@@ -228,7 +614,7 @@ impl Repository for SqliteRepository {
 
         let mut rows = connection
             .query(
-                "SELECT Id, RssFeedId, ChannelId  FROM Subscriptions WHERE RssFeedId = (?1)",
+                "SELECT Id, RssFeedId, ChannelId, Mention, CreateThreads, ThreadArchiveMinutes FROM Subscriptions WHERE RssFeedId = (?1)",
                 params!(feed_id),
             )
             .await?;
@@ -236,25 +622,20 @@ impl Repository for SqliteRepository {
         let mut subs: Vec<Subscription> = vec![];
 
         while let Some(row) = rows.next().await? {
-            subs.push(Subscription {
-                id: row.get(0).unwrap(),
-                rss_feed_id: row.get(1).unwrap(),
-                channel_id: row.get(2).unwrap(),
-            })
+            subs.push(read_subscription(&row)?)
         }
 
-        if subs.len() == 0 {
+        if subs.is_empty() {
             return Ok(None);
         }
 
         Ok(Some(subs))
     }
 
-    async fn get_feed_id(&self, feed_url: &str) -> Result<i64, Error> {
+    async fn get_feed_id(&self, feed_url: &str) -> Result<Option<i64>, Error> {
         let database = Builder::new_local(DATABASE_PATH).build().await?;
         let connection = database.connect()?;
 
-        // Get the identifier of the just inserted URL
         let mut rows = connection
             .query(
                 "SELECT Id FROM RssFeeds WHERE Url = (?1)",
@@ -262,27 +643,1003 @@ impl Repository for SqliteRepository {
             )
             .await?;
 
-        let row_option = rows.next().await?;
+        match rows.next().await? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
 
-        match row_option {
-            Some(row) => {
-                let id_value = row.get_value(0)?;
-                let int_option = id_value.as_integer();
+    async fn save_digest_schedule(&self, schedule: &DigestSchedule) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
 
-                match int_option {
-                    Some(int) => {
-                        return Ok(*int);
-                    }
-                    None => {
-                        return Err(anyhow!("No feed_url in database"));
-                    }
-                }
-            }
-            None => {
-                return Err(anyhow!(
-                    "No RssFeeds entry with the given URL exists in the database"
-                ))
-            }
+        let weekday = schedule
+            .weekday
+            .map(|weekday| weekday.num_days_from_monday());
+
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO DigestSchedules (ChannelId, Frequency, Weekday, Time, Timezone, NextRun) values (?1, ?2, ?3, ?4, ?5, ?6)",
+                params!(
+                    schedule.channel_id,
+                    schedule.frequency.as_str(),
+                    weekday,
+                    schedule.time.format("%H:%M").to_string(),
+                    schedule.timezone.name(),
+                    converter::format_sqlite_date(&schedule.next_run)
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_digest_schedule(&self, channel_id: &u64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "DELETE FROM DigestSchedules WHERE ChannelId = (?1)",
+                params!(channel_id),
+            )
+            .await?;
+
+        connection
+            .execute(
+                "DELETE FROM DigestItems WHERE ChannelId = (?1)",
+                params!(channel_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_digest_schedule(&self, channel_id: &u64) -> Result<Option<DigestSchedule>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT ChannelId, Frequency, Weekday, Time, Timezone, NextRun FROM DigestSchedules WHERE ChannelId = (?1)",
+                params!(channel_id),
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(read_digest_schedule(&row)?)),
+            None => Ok(None),
         }
     }
+
+    async fn get_due_digest_schedules(&self, now: &str) -> Result<Vec<DigestSchedule>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT ChannelId, Frequency, Weekday, Time, Timezone, NextRun FROM DigestSchedules WHERE NextRun <= (?1)",
+                params!(now),
+            )
+            .await?;
+
+        let mut schedules = vec![];
+
+        while let Some(row) = rows.next().await? {
+            schedules.push(read_digest_schedule(&row)?);
+        }
+
+        Ok(schedules)
+    }
+
+    async fn update_digest_next_run(&self, channel_id: &u64, next_run: &str) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE DigestSchedules SET NextRun = (?1) WHERE ChannelId = (?2)",
+                params!(next_run, channel_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_digest_item(&self, channel_id: &u64, item: &RssItem) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO DigestItems (ChannelId, Guid, Title, Link, Reviewer, UserRating, PubDate) values (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params!(
+                    channel_id,
+                    item.guid.as_str(),
+                    item.title.as_str(),
+                    item.link.as_str(),
+                    item.reviewer.as_str(),
+                    item.user_rating,
+                    converter::format_sqlite_date(&item.pub_date)
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_digest_items(&self, channel_id: &u64) -> Result<Vec<DigestItem>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT Id, ChannelId, Title, Link, Reviewer, UserRating, PubDate FROM DigestItems WHERE ChannelId = (?1) ORDER BY PubDate ASC",
+                params!(channel_id),
+            )
+            .await?;
+
+        let mut items = vec![];
+
+        while let Some(row) = rows.next().await? {
+            items.push(DigestItem {
+                id: row.get(0)?,
+                channel_id: row.get(1)?,
+                title: row.get_str(2)?.to_string(),
+                link: row.get_str(3)?.to_string(),
+                reviewer: row.get_str(4)?.to_string(),
+                user_rating: row.get::<i64>(5)? as i8,
+                pub_date: converter::parse_sqlite_date(row.get_str(6)?)?,
+            });
+        }
+
+        Ok(items)
+    }
+
+    async fn delete_digest_items(&self, channel_id: &u64, ids: &[i64]) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        // Only remove the items that made it into the digest, new ones may have arrived since
+        for id in ids {
+            connection
+                .execute(
+                    "DELETE FROM DigestItems WHERE ChannelId = (?1) AND Id = (?2)",
+                    params!(channel_id, id),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_cache_entry(&self, key: &str, now: &str) -> Result<Option<CacheEntry>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT Value, ExpiresAt FROM ScrapeCache WHERE Key = (?1) AND ExpiresAt > (?2)",
+                params!(key, now),
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(read_cache_entry(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn save_cache_entry(&self, key: &str, value: &str, expires_at: &str) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO ScrapeCache (Key, Value, ExpiresAt) values (?1, ?2, ?3)",
+                params!(key, value, expires_at),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_sub(&self, feed_id: &i64, channel_id: &u64) -> Result<Option<Subscription>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT Id, RssFeedId, ChannelId, Mention, CreateThreads, ThreadArchiveMinutes FROM Subscriptions WHERE RssFeedId = (?1) AND ChannelId = (?2)",
+                params!(feed_id, channel_id),
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(read_subscription(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn update_sub_mention(
+        &self,
+        feed_id: &i64,
+        channel_id: &u64,
+        mention: Option<&Mention>,
+    ) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE Subscriptions SET Mention = (?1) WHERE RssFeedId = (?2) AND ChannelId = (?3)",
+                params!(
+                    mention.map(|mention| mention.to_storage_string()),
+                    feed_id,
+                    channel_id
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_sub_threads(
+        &self,
+        feed_id: &i64,
+        channel_id: &u64,
+        create_threads: bool,
+        archive_minutes: Option<u16>,
+    ) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE Subscriptions SET CreateThreads = (?1), ThreadArchiveMinutes = (?2) WHERE RssFeedId = (?3) AND ChannelId = (?4)",
+                params!(create_threads, archive_minutes, feed_id, channel_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_notify_user(&self, feed_id: &i64, channel_id: &u64, user_id: &u64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO NotifyUsers (RssFeedId, ChannelId, UserId) values (?1, ?2, ?3)",
+                params!(feed_id, channel_id, user_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_notify_user(&self, feed_id: &i64, channel_id: &u64, user_id: &u64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "DELETE FROM NotifyUsers WHERE RssFeedId = (?1) AND ChannelId = (?2) AND UserId = (?3)",
+                params!(feed_id, channel_id, user_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_notify_users(&self, feed_id: &i64, channel_id: &u64) -> Result<Vec<u64>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT UserId FROM NotifyUsers WHERE RssFeedId = (?1) AND ChannelId = (?2)",
+                params!(feed_id, channel_id),
+            )
+            .await?;
+
+        let mut user_ids = vec![];
+
+        while let Some(row) = rows.next().await? {
+            user_ids.push(row.get(0)?);
+        }
+
+        Ok(user_ids)
+    }
+
+    async fn save_delivered_review(&self, review: &DeliveredReview) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "INSERT INTO DeliveredReviews (Guid, RssFeedId, ChannelId, MessageId, ViaWebhook, Link, PubDate, Footer, Description) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params!(
+                    review.guid.as_str(),
+                    review.rss_feed_id,
+                    review.channel_id,
+                    review.message_id,
+                    review.via_webhook,
+                    review.link.as_str(),
+                    converter::format_sqlite_date(&review.pub_date),
+                    review.footer.as_str(),
+                    review.description.as_str()
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_delivered_reviews(&self, guid: &str) -> Result<Vec<DeliveredReview>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT Id, Guid, RssFeedId, ChannelId, MessageId, ViaWebhook, Link, PubDate, Footer, Description, MissingPolls FROM DeliveredReviews WHERE Guid = (?1) AND Removed = 0",
+                params!(guid),
+            )
+            .await?;
+
+        let mut reviews = vec![];
+
+        while let Some(row) = rows.next().await? {
+            reviews.push(read_delivered_review(&row)?);
+        }
+
+        Ok(reviews)
+    }
+
+    async fn get_recent_delivered_reviews(&self, since: &str) -> Result<Vec<DeliveredReview>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT Id, Guid, RssFeedId, ChannelId, MessageId, ViaWebhook, Link, PubDate, Footer, Description, MissingPolls FROM DeliveredReviews WHERE PubDate >= (?1) AND Removed = 0",
+                params!(since),
+            )
+            .await?;
+
+        let mut reviews = vec![];
+
+        while let Some(row) = rows.next().await? {
+            reviews.push(read_delivered_review(&row)?);
+        }
+
+        Ok(reviews)
+    }
+
+    async fn update_delivered_review(&self, id: &i64, footer: &str, description: &str) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE DeliveredReviews SET Footer = (?1), Description = (?2) WHERE Id = (?3)",
+                params!(footer, description, id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_feed_delivered_reviews(&self, feed_id: &i64, since: &str) -> Result<Vec<DeliveredReview>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT Id, Guid, RssFeedId, ChannelId, MessageId, ViaWebhook, Link, PubDate, Footer, Description, MissingPolls FROM DeliveredReviews WHERE RssFeedId = (?1) AND PubDate >= (?2) AND Removed = 0",
+                params!(feed_id, since),
+            )
+            .await?;
+
+        let mut reviews = vec![];
+
+        while let Some(row) = rows.next().await? {
+            reviews.push(read_delivered_review(&row)?);
+        }
+
+        Ok(reviews)
+    }
+
+    async fn update_delivered_review_missing_polls(&self, id: &i64, missing_polls: &i64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE DeliveredReviews SET MissingPolls = (?1) WHERE Id = (?2)",
+                params!(missing_polls, id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn mark_delivered_review_removed(&self, id: &i64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE DeliveredReviews SET Removed = 1 WHERE Id = (?1)",
+                params!(id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_delivered_review(&self, id: &i64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute("DELETE FROM DeliveredReviews WHERE Id = (?1)", params!(id))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_guild_settings(&self, guild_id: &u64) -> Result<GuildSettings, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT GuildId, DeletedReviewAction, UnfurlLinks FROM GuildSettings WHERE GuildId = (?1)",
+                params!(guild_id),
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(GuildSettings {
+                guild_id: row.get(0)?,
+                deleted_review_action: row.get_str(1)?.parse()?,
+                unfurl_links: row.get(2)?,
+            }),
+            // Guilds that never changed a setting get the defaults
+            None => Ok(GuildSettings {
+                guild_id: *guild_id,
+                deleted_review_action: DeletedReviewAction::Mark,
+                unfurl_links: false,
+            }),
+        }
+    }
+
+    async fn save_guild_settings(&self, settings: &GuildSettings) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO GuildSettings (GuildId, DeletedReviewAction, UnfurlLinks) values (?1, ?2, ?3)",
+                params!(
+                    settings.guild_id,
+                    settings.deleted_review_action.as_str(),
+                    settings.unfurl_links
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_review(&self, feed_id: &i64, item: &RssItem) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        // Reviews can be edited, so keep what the feed shows up to date. Unchanged reviews are
+        // skipped so every poll doesn't rewrite them and their search index entries.
+        connection
+            .execute(
+                "INSERT INTO Reviews (Guid, RssFeedId, Title, Link, Reviewer, UserRating, PubDate, Description, ImageUrl) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9) ON CONFLICT(Guid) DO UPDATE SET Title = excluded.Title, UserRating = excluded.UserRating, Description = excluded.Description, ImageUrl = excluded.ImageUrl WHERE excluded.Title IS NOT Title OR excluded.UserRating IS NOT UserRating OR excluded.Description IS NOT Description OR excluded.ImageUrl IS NOT ImageUrl",
+                params!(
+                    item.guid.as_str(),
+                    feed_id,
+                    item.title.as_str(),
+                    item.link.as_str(),
+                    item.reviewer.as_str(),
+                    item.user_rating,
+                    converter::format_sqlite_date(&item.pub_date),
+                    item.description.as_str(),
+                    item.image.url.as_str()
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_review_metadata(
+        &self,
+        link: &str,
+        likes: Option<i64>,
+        comments: Option<i64>,
+        status: Option<&str>,
+    ) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE Reviews SET Likes = (?1), Comments = (?2), Status = (?3) WHERE Link = (?4)",
+                params!(likes, comments, status, link),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_feed_reviews(&self, feed_id: &i64, since: &str) -> Result<Vec<StoredReview>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT Guid, RssFeedId, Title, Link, Reviewer, UserRating, PubDate, Description, ImageUrl, Likes, Comments, Status FROM Reviews WHERE RssFeedId = (?1) AND PubDate >= (?2) ORDER BY PubDate ASC",
+                params!(feed_id, since),
+            )
+            .await?;
+
+        let mut reviews = vec![];
+
+        while let Some(row) = rows.next().await? {
+            reviews.push(read_stored_review(&row)?);
+        }
+
+        Ok(reviews)
+    }
+
+    async fn find_reviews(&self, query: &ReviewQuery) -> Result<Vec<StoredReview>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let feed_ids = query.feed_ids.as_ref().map(|feed_ids| {
+            format!(
+                "[{}]",
+                feed_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            )
+        });
+
+        // Filters that are None are NULL here, which turns their condition off. A negative limit
+        // is no limit.
+        let mut rows = connection
+            .query(
+                r#"SELECT Guid, RssFeedId, Title, Link, Reviewer, UserRating, PubDate, Description, ImageUrl, Likes, Comments, Status FROM Reviews
+                    WHERE ((?1) IS NULL OR Reviewer = (?1) COLLATE NOCASE)
+                    AND ((?2) IS NULL OR Title LIKE (?2) ESCAPE '\')
+                    AND ((?3) IS NULL OR PubDate >= (?3))
+                    AND ((?4) IS NULL OR PubDate < (?4))
+                    AND ((?5) IS NULL OR UserRating >= (?5))
+                    AND ((?6) IS NULL OR UserRating <= (?6))
+                    AND ((?8) IS NULL OR RssFeedId IN (SELECT value FROM json_each(?8)))
+                    ORDER BY PubDate DESC LIMIT coalesce((?7), -1)"#,
+                params!(
+                    query.reviewer.as_deref(),
+                    query.game.as_deref().map(converter::to_like_pattern),
+                    query.since.as_ref().map(converter::format_sqlite_date),
+                    query.until.as_ref().map(converter::format_sqlite_date),
+                    query.min_rating,
+                    query.max_rating,
+                    query.limit,
+                    feed_ids
+                ),
+            )
+            .await?;
+
+        let mut reviews = vec![];
+
+        while let Some(row) = rows.next().await? {
+            reviews.push(read_stored_review(&row)?);
+        }
+
+        Ok(reviews)
+    }
+
+    async fn search_reviews(&self, search: &ReviewSearch) -> Result<Vec<ReviewSearchResult>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let feed_ids = format!(
+            "[{}]",
+            search
+                .feed_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        );
+
+        // Title matches are weighted above description matches. Filters that are None are NULL
+        // here, which turns their condition off.
+        let mut rows = connection
+            .query(
+                r#"SELECT r.Guid, r.RssFeedId, r.Title, r.Link, r.Reviewer, r.UserRating, r.PubDate, r.Description, r.ImageUrl, r.Likes, r.Comments, r.Status,
+                        snippet(ReviewsSearch, 1, '**', '**', '…', 24)
+                    FROM ReviewsSearch JOIN Reviews r ON r.Id = ReviewsSearch.rowid
+                    WHERE ReviewsSearch MATCH (?1)
+                    AND r.RssFeedId IN (SELECT value FROM json_each(?2))
+                    AND ((?3) IS NULL OR r.Reviewer = (?3) COLLATE NOCASE)
+                    AND ((?4) IS NULL OR r.PubDate >= (?4))
+                    AND ((?5) IS NULL OR r.PubDate < (?5))
+                    AND ((?6) IS NULL OR r.UserRating >= (?6))
+                    AND ((?7) IS NULL OR r.UserRating <= (?7))
+                    ORDER BY bm25(ReviewsSearch, 10.0, 1.0) LIMIT (?8)"#,
+                params!(
+                    search.fts_query.as_str(),
+                    feed_ids,
+                    search.reviewer.as_deref(),
+                    search.since.as_ref().map(converter::format_sqlite_date),
+                    search.until.as_ref().map(converter::format_sqlite_date),
+                    search.min_rating,
+                    search.max_rating,
+                    search.limit
+                ),
+            )
+            .await?;
+
+        let mut results = vec![];
+
+        while let Some(row) = rows.next().await? {
+            results.push(ReviewSearchResult {
+                review: read_stored_review(&row)?,
+                snippet: row.get(12)?,
+            });
+        }
+
+        Ok(results)
+    }
+
+    async fn get_channel_feed_ids(&self, channel_ids: &[u64]) -> Result<Vec<i64>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let channel_ids = format!(
+            "[{}]",
+            channel_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        );
+
+        // A feed subscribed in several of the channels is only returned once
+        let mut rows = connection
+            .query(
+                "SELECT DISTINCT RssFeedId FROM Subscriptions WHERE ChannelId IN (SELECT value FROM json_each(?1))",
+                params!(channel_ids),
+            )
+            .await?;
+
+        let mut feed_ids = vec![];
+
+        while let Some(row) = rows.next().await? {
+            feed_ids.push(row.get(0)?);
+        }
+
+        Ok(feed_ids)
+    }
+
+    async fn save_recap_channel(&self, channel_id: &u64, last_recap: &str) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        // Opting in again shouldn't repeat a recap that was already posted
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO RecapChannels (ChannelId, LastRecap) values (?1, ?2)",
+                params!(channel_id, last_recap),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_recap_channel(&self, channel_id: &u64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "DELETE FROM RecapChannels WHERE ChannelId = (?1)",
+                params!(channel_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_recap_channels(&self) -> Result<Vec<RecapChannel>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query("SELECT ChannelId, LastRecap FROM RecapChannels", params!())
+            .await?;
+
+        let mut channels = vec![];
+
+        while let Some(row) = rows.next().await? {
+            channels.push(RecapChannel {
+                channel_id: row.get(0)?,
+                last_recap: converter::parse_sqlite_date(row.get_str(1)?)?,
+            });
+        }
+
+        Ok(channels)
+    }
+
+    async fn update_recap_channel(&self, channel_id: &u64, last_recap: &str) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE RecapChannels SET LastRecap = (?1) WHERE ChannelId = (?2)",
+                params!(last_recap, channel_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_guild_linked_accounts(&self, guild_id: &u64) -> Result<Vec<LinkedAccount>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT LinkedAccounts.UserId, Username, VerificationCode, Verified FROM LinkedAccounts
+                    INNER JOIN LinkedAccountGuilds ON LinkedAccountGuilds.UserId = LinkedAccounts.UserId
+                    WHERE LinkedAccountGuilds.GuildId = (?1) AND Verified = 1",
+                params!(guild_id),
+            )
+            .await?;
+
+        let mut accounts = vec![];
+
+        while let Some(row) = rows.next().await? {
+            accounts.push(read_linked_account(&row)?);
+        }
+
+        Ok(accounts)
+    }
+
+    async fn get_linked_account(&self, user_id: &u64) -> Result<Option<LinkedAccount>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT UserId, Username, VerificationCode, Verified FROM LinkedAccounts WHERE UserId = (?1)",
+                params!(user_id),
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(read_linked_account(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn save_linked_account(&self, account: &LinkedAccount) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO LinkedAccounts (UserId, Username, VerificationCode, Verified) values (?1, ?2, ?3, ?4)",
+                params!(
+                    account.user_id,
+                    account.username.as_str(),
+                    account.verification_code.as_str(),
+                    account.verified
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_linked_account(&self, user_id: &u64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "DELETE FROM LinkedAccounts WHERE UserId = (?1)",
+                params!(user_id),
+            )
+            .await?;
+
+        connection
+            .execute(
+                "DELETE FROM LinkedAccountGuilds WHERE UserId = (?1)",
+                params!(user_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_linked_account_guild(&self, user_id: &u64, guild_id: &u64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO LinkedAccountGuilds (UserId, GuildId) values (?1, ?2)",
+                params!(user_id, guild_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_webhook_channel(&self, channel_id: &u64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO ChannelWebhooks (ChannelId) values (?1)",
+                params!(channel_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_webhook_channel(&self, channel_id: &u64) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "DELETE FROM ChannelWebhooks WHERE ChannelId = (?1)",
+                params!(channel_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_webhook_channel(&self, channel_id: &u64) -> Result<Option<ChannelWebhook>, Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        let mut rows = connection
+            .query(
+                "SELECT ChannelId, WebhookId, WebhookToken FROM ChannelWebhooks WHERE ChannelId = (?1)",
+                params!(channel_id),
+            )
+            .await?;
+
+        match rows.next().await? {
+            Some(row) => Ok(Some(ChannelWebhook {
+                channel_id: row.get(0)?,
+                webhook_id: row.get(1)?,
+                encrypted_token: row.get(2)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn update_channel_webhook(
+        &self,
+        channel_id: &u64,
+        webhook_id: &u64,
+        encrypted_token: &str,
+    ) -> Result<(), Error> {
+        let database = Builder::new_local(DATABASE_PATH).build().await?;
+        let connection = database.connect()?;
+
+        connection
+            .execute(
+                "UPDATE ChannelWebhooks SET WebhookId = (?1), WebhookToken = (?2) WHERE ChannelId = (?3)",
+                params!(webhook_id, encrypted_token, channel_id),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn read_delivered_review(row: &libsql::Row) -> Result<DeliveredReview, Error> {
+    Ok(DeliveredReview {
+        id: row.get(0)?,
+        guid: row.get(1)?,
+        rss_feed_id: row.get(2)?,
+        channel_id: row.get(3)?,
+        message_id: row.get(4)?,
+        via_webhook: row.get(5)?,
+        link: row.get(6)?,
+        pub_date: converter::parse_sqlite_date(row.get_str(7)?)?,
+        footer: row.get(8)?,
+        description: row.get(9)?,
+        missing_polls: row.get(10)?,
+    })
+}
+
+fn read_stored_review(row: &libsql::Row) -> Result<StoredReview, Error> {
+    Ok(StoredReview {
+        guid: row.get(0)?,
+        rss_feed_id: row.get(1)?,
+        title: row.get(2)?,
+        link: row.get(3)?,
+        reviewer: row.get(4)?,
+        user_rating: row.get::<i64>(5)? as i8,
+        pub_date: converter::parse_sqlite_date(row.get_str(6)?)?,
+        description: row.get(7)?,
+        image_url: row.get(8)?,
+        likes: row.get(9)?,
+        comments: row.get(10)?,
+        status: row.get(11)?,
+    })
+}
+
+fn read_cache_entry(row: &libsql::Row) -> Result<CacheEntry, Error> {
+    Ok(CacheEntry {
+        value: row.get(0)?,
+        expires_at: converter::parse_sqlite_date(row.get_str(1)?)?,
+    })
+}
+
+fn read_linked_account(row: &libsql::Row) -> Result<LinkedAccount, Error> {
+    Ok(LinkedAccount {
+        user_id: row.get(0)?,
+        username: row.get(1)?,
+        verification_code: row.get(2)?,
+        verified: row.get(3)?,
+    })
+}
+
+fn read_subscription(row: &libsql::Row) -> Result<Subscription, Error> {
+    let mention = match row.get::<Option<String>>(3)? {
+        Some(value) => Some(value.parse()?),
+        None => None,
+    };
+
+    Ok(Subscription {
+        id: row.get(0)?,
+        rss_feed_id: row.get(1)?,
+        channel_id: row.get(2)?,
+        mention,
+        create_threads: row.get(4)?,
+        thread_archive_minutes: row.get::<Option<u32>>(5)?.map(|minutes| minutes as u16),
+    })
+}
+
+fn read_digest_schedule(row: &libsql::Row) -> Result<DigestSchedule, Error> {
+    let weekday = match row.get::<Option<i64>>(2)? {
+        Some(value) => Some(
+            Weekday::try_from(value as u8).map_err(|_| anyhow!("Invalid DigestSchedules.Weekday"))?,
+        ),
+        None => None,
+    };
+
+    Ok(DigestSchedule {
+        channel_id: row.get(0)?,
+        frequency: row.get_str(1)?.parse()?,
+        weekday,
+        time: converter::parse_digest_time(row.get_str(3)?)?,
+        timezone: row
+            .get_str(4)?
+            .parse()
+            .map_err(|_| anyhow!("Invalid DigestSchedules.Timezone"))?,
+        next_run: converter::parse_sqlite_date(row.get_str(5)?)?,
+    })
 }
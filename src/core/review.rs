@@ -0,0 +1,74 @@
+use super::parser::RssItem;
+
+/// Finds the user's most recent review of a game in their feed. An exact title match wins over a
+/// partial one, so "Doom" doesn't pick a review of "Doom Eternal" when both are in the feed.
+pub fn find_feed_review<'a>(items: &'a [RssItem], game_title: &str) -> Option<&'a RssItem> {
+    let game_title = game_title.trim().to_lowercase();
+
+    if game_title.is_empty() {
+        return None;
+    }
+
+    let most_recent =
+        |matches: Vec<&'a RssItem>| matches.into_iter().max_by_key(|item| item.pub_date);
+
+    let exact_matches: Vec<&RssItem> = items
+        .iter()
+        .filter(|item| item.title.trim().to_lowercase() == game_title)
+        .collect();
+
+    if !exact_matches.is_empty() {
+        return most_recent(exact_matches);
+    }
+
+    most_recent(
+        items
+            .iter()
+            .filter(|item| item.title.to_lowercase().contains(&game_title))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser;
+
+    fn build_item(title: &str, day: u32) -> RssItem {
+        RssItem {
+            title: title.to_string(),
+            link: format!("https://backloggd.com/u/username1/review/{}/", day),
+            ..parser::build_test_item(&format!("review-{}", day), day)
+        }
+    }
+
+    #[test]
+    fn find_feed_review_prefers_exact_title() {
+        let items = vec![build_item("Doom Eternal", 2), build_item("DOOM", 1)];
+
+        let actual = find_feed_review(&items, "doom");
+
+        assert_eq!(actual.map(|item| item.guid.as_str()), Some("review-1"));
+    }
+
+    #[test]
+    fn find_feed_review_returns_most_recent_partial_match() {
+        let items = vec![
+            build_item("Hollow Knight", 1),
+            build_item("Hollow Knight: Silksong", 3),
+            build_item("Celeste", 2),
+        ];
+
+        let actual = find_feed_review(&items, "hollow");
+
+        assert_eq!(actual.map(|item| item.guid.as_str()), Some("review-3"));
+    }
+
+    #[test]
+    fn find_feed_review_returns_none_when_not_reviewed() {
+        let items = vec![build_item("Celeste", 1)];
+
+        assert!(find_feed_review(&items, "Hollow Knight").is_none());
+        assert!(find_feed_review(&items, " ").is_none());
+    }
+}
@@ -1,80 +1,192 @@
 use anyhow::anyhow;
 use anyhow::Error;
-use reqwest::{Client, StatusCode};
+use reqwest::header::{
+    HeaderMap, HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Client, RequestBuilder, StatusCode};
 use scraper::Html;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::warn;
+
+use super::http;
+use super::http::{HttpConfig, Throttle, ThrottledResponse};
+use super::parser::{self, RssItem};
 
 pub trait Scraper {
     fn get_rss_feed_content(&self, url: &RssRequest) -> impl std::future::Future<Output = Result<RssResponse, Error>>;
     fn get_profile_pic_url_or_default(&self, profile_url: &str) -> impl std::future::Future<Output = Option<String>>;
     fn get_review_metadata(&self, review_url: &str) -> impl std::future::Future<Output = Option<ReviewMetadata>>;
-    fn does_feed_exist(&self, feed_url: &str) -> impl std::future::Future<Output = Result<bool, anyhow::Error>>;
+    fn fetch_feed(&self, feed_url: &str) -> impl std::future::Future<Output = Result<String, FeedStatus>>;
+    fn is_review_deleted(&self, review_url: &str) -> impl std::future::Future<Output = bool>;
+    fn get_page_preview(&self, page_url: &str) -> impl std::future::Future<Output = Option<PagePreview>>;
+    fn search_games(&self, query: &str) -> impl std::future::Future<Output = Result<Vec<GameSearchResult>, Error>>;
+    fn get_game_details(&self, game_url: &str) -> impl std::future::Future<Output = Option<GameDetails>>;
+    fn get_user_profile(&self, username: &str) -> impl std::future::Future<Output = Result<Option<UserProfile>, Error>>;
+    fn get_game_log(&self, username: &str, game_url: &str) -> impl std::future::Future<Output = Result<Option<GameLog>, Error>>;
+
+    /// Scrapes the review's metadata without using any cached copy, scrapers that don't cache
+    /// scrape it as usual.
+    fn refresh_review_metadata(
+        &self,
+        review_url: &str,
+    ) -> impl std::future::Future<Output = Option<ReviewMetadata>> {
+        self.get_review_metadata(review_url)
+    }
 }
 
 pub struct RssResponse {
     pub content: Option<String>,
     pub etag: Option<String>,
+    pub last_modified: Option<String>,
 }
 
+/// The validators come from the last response for the feed. Without either the full feed is
+/// always fetched.
 pub struct RssRequest {
     pub url: String,
-    pub etag: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl RssRequest {
+    /// A request without validators, for commands that always need the whole feed.
+    pub fn new(url: &str) -> Self {
+        return Self {
+            url: url.to_string(),
+            etag: None,
+            last_modified: None,
+        };
+    }
+}
+
+/// Why a feed couldn't be fetched. Only NotFound means the user doesn't exist, the others mean
+/// Backloggd couldn't tell us.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeedStatus {
+    NotFound,
+    RateLimited,
+    Unavailable,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReviewMetadata {
     pub likes: Option<String>,
     pub comments: Option<String>,
     pub status: Option<String>,
 }
 
+/// What a Backloggd review, game or profile page says about itself, used to unfurl links.
+pub struct PagePreview {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub avatar_url: Option<String>,
+    // Only review pages have likes, comments and a play status
+    pub metadata: ReviewMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSearchResult {
+    pub title: String,
+    pub year: Option<String>,
+    pub url: String,
+}
+
+pub struct GameDetails {
+    pub title: String,
+    pub url: String,
+    pub cover_url: Option<String>,
+    pub release_date: Option<String>,
+    pub platforms: Vec<String>,
+    // Out of 5 stars
+    pub average_rating: Option<f32>,
+    // Number of ratings for each half star from ½★ to ★★★★★
+    pub rating_distribution: Vec<u32>,
+    pub plays: GamePlays,
+}
+
+// Backloggd abbreviates these, e.g. 2.4K, so they're kept as displayed
+pub struct GamePlays {
+    pub played: Option<String>,
+    pub playing: Option<String>,
+    pub backlogs: Option<String>,
+    pub wishlists: Option<String>,
+}
+
+pub struct UserProfile {
+    pub username: String,
+    pub url: String,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    // Counts are kept as displayed, e.g. 1,048
+    pub games_played: Option<String>,
+    pub backlog: Option<String>,
+    pub reviews: Option<String>,
+    pub favorite_games: Vec<String>,
+    pub playing: Vec<String>,
+}
+
+/// A user's log entry for a single game.
+pub struct GameLog {
+    pub title: String,
+    pub url: String,
+    // Half stars out of 10, like RSS ratings
+    pub rating: Option<i8>,
+    pub status: Option<String>,
+    pub review_url: Option<String>,
+}
+
+// Clones share the connection pool and the throttle
+#[derive(Debug, Clone)]
 pub struct ReqwestScraper {
     client: Client,
+    config: HttpConfig,
+    throttle: Arc<Throttle>,
 }
 
 impl ReqwestScraper {
-    pub fn new(client: Client) -> Self {
-        return ReqwestScraper { client };
+    pub fn new(config: HttpConfig) -> Result<Self, Error> {
+        let client = http::build_client(&config)?;
+        let throttle = Arc::new(Throttle::new(&config));
+        return Ok(ReqwestScraper {
+            client,
+            config,
+            throttle,
+        });
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<ThrottledResponse, reqwest::Error> {
+        http::send_with_retry(request, &self.config, &self.throttle).await
     }
 }
 
 impl Scraper for ReqwestScraper {
     async fn get_rss_feed_content(&self, request: &RssRequest) -> Result<RssResponse, Error> {
-        let response = self
-            .client
-            .get(&request.url)
-            .header("If-None-Match", &request.etag)
-            .send()
-            .await?;
+        let mut builder = self.client.get(&request.url);
+
+        for (name, value) in build_conditional_headers(request) {
+            builder = builder.header(name, value);
+        }
+
+        let response = self.send(builder).await?;
 
-        let headers = response.headers();
+        let etag = read_validator(response.headers(), ETAG);
+        let last_modified = read_validator(response.headers(), LAST_MODIFIED);
 
         if response.status() == StatusCode::OK {
-            match headers.get("etag") {
-                Some(value) => {
-                    let etag = String::from(value.to_str()?);
-                    Ok(RssResponse {
-                        content: Some(response.text().await?),
-                        etag: Some(etag),
-                    })
-                }
-                None => Ok(RssResponse {
-                    content: Some(response.text().await?),
-                    etag: None,
-                }),
-            }
+            Ok(RssResponse {
+                content: Some(response.text().await?),
+                etag,
+                last_modified,
+            })
         } else if response.status() == StatusCode::NOT_MODIFIED {
-            match headers.get("etag") {
-                Some(value) => {
-                    let etag = String::from(value.to_str()?);
-                    Ok(RssResponse {
-                        content: None,
-                        etag: Some(etag),
-                    })
-                }
-                None => Ok(RssResponse {
-                    content: None,
-                    etag: None,
-                }),
-            }
+            // A 304 doesn't have to repeat the validators, the ones we sent are still current
+            Ok(RssResponse {
+                content: None,
+                etag: etag.or(request.etag.clone()),
+                last_modified: last_modified.or(request.last_modified.clone()),
+            })
         } else {
             return Err(anyhow!(
                 "Unexpected HTTP status code not (Not 200 or 304) while fetching {}",
@@ -84,7 +196,7 @@ impl Scraper for ReqwestScraper {
     }
 
     async fn get_profile_pic_url_or_default(&self, profile_url: &str) -> Option<String> {
-        if let Ok(response) = self.client.get(profile_url).send().await {
+        if let Ok(response) = self.send(self.client.get(profile_url)).await {
             if response.status() == StatusCode::OK {
                 if let Ok(content) = response.text().await {
                     if let Some(url) = parse_profile_pic_url(&content) {
@@ -98,7 +210,7 @@ impl Scraper for ReqwestScraper {
     }
 
     async fn get_review_metadata(&self, review_url: &str) -> Option<ReviewMetadata> {
-        if let Ok(response) = self.client.get(review_url).send().await {
+        if let Ok(response) = self.send(self.client.get(review_url)).await {
             if response.status() == StatusCode::OK {
                 if let Ok(content) = response.text().await {
                     return Some(parse_review_metadata(&content));
@@ -109,15 +221,475 @@ impl Scraper for ReqwestScraper {
         None
     }
 
-    async fn does_feed_exist(&self, feed_url: &str) -> Result<bool, anyhow::Error> {
-        let response = self.client.head(feed_url).send().await?;
+    async fn fetch_feed(&self, feed_url: &str) -> Result<String, FeedStatus> {
+        // Only the body shows it's a Backloggd feed, and callers want the body anyway, so one GET
+        // does both
+        let response = match self.send(self.client.get(feed_url)).await {
+            Ok(response) => response,
+            Err(error) => {
+                warn!({ feed_url = feed_url, error = ?error }, "Error fetching feed");
+                return Err(FeedStatus::Unavailable);
+            }
+        };
 
-        if response.status() == StatusCode::OK {
-            return Ok(true);
+        if let Some(status) = feed_status_from_code(response.status()) {
+            return Err(status);
+        }
+
+        match response.text().await {
+            Ok(content) if parser::is_backloggd_feed(&content) => Ok(content),
+            Ok(_) => Err(FeedStatus::NotFound),
+            Err(_) => Err(FeedStatus::Unavailable),
+        }
+    }
+
+    async fn get_page_preview(&self, page_url: &str) -> Option<PagePreview> {
+        if let Ok(response) = self.send(self.client.get(page_url)).await {
+            if response.status() == StatusCode::OK {
+                if let Ok(content) = response.text().await {
+                    return Some(parse_page_preview(&content));
+                }
+            }
+        }
+
+        None
+    }
+
+    async fn search_games(&self, query: &str) -> Result<Vec<GameSearchResult>, Error> {
+        let response = self
+            .send(self.client.get(build_game_search_url(query)?))
+            .await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(anyhow!(
+                "Unexpected HTTP status code {} while searching games",
+                response.status()
+            ));
+        }
+
+        Ok(parse_game_search_results(&response.text().await?))
+    }
+
+    async fn get_game_details(&self, game_url: &str) -> Option<GameDetails> {
+        if let Ok(response) = self.send(self.client.get(game_url)).await {
+            if response.status() == StatusCode::OK {
+                if let Ok(content) = response.text().await {
+                    return parse_game_details(&content, game_url);
+                }
+            }
+        }
+
+        None
+    }
+
+    async fn get_user_profile(&self, username: &str) -> Result<Option<UserProfile>, Error> {
+        let profile_url = build_profile_url(username);
+        let response = self.send(self.client.get(&profile_url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(Some(parse_user_profile(
+                &response.text().await?,
+                username,
+                &profile_url,
+            ))),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => Err(anyhow!(
+                "Unexpected HTTP status code {} while fetching {}",
+                status,
+                profile_url
+            )),
+        }
+    }
+
+    async fn get_game_log(&self, username: &str, game_url: &str) -> Result<Option<GameLog>, Error> {
+        let log_url = build_game_log_url(username, game_url)
+            .ok_or(anyhow!("Unable to find the game in {}", game_url))?;
+        let response = self.send(self.client.get(&log_url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(parse_game_log(&response.text().await?, &log_url)),
+            // The user hasn't logged the game
+            StatusCode::NOT_FOUND => Ok(None),
+            status => Err(anyhow!(
+                "Unexpected HTTP status code {} while fetching {}",
+                status,
+                log_url
+            )),
+        }
+    }
+
+    async fn is_review_deleted(&self, review_url: &str) -> bool {
+        // Anything other than a 404 could be a temporary problem, so don't treat it as deleted
+        match self.send(self.client.get(review_url)).await {
+            Ok(response) => response.status() == StatusCode::NOT_FOUND,
+            Err(_) => false,
+        }
+    }
+}
+
+pub fn parse_page_preview(html: &str) -> PagePreview {
+    let document = Html::parse_document(html);
+
+    PagePreview {
+        title: parse_meta_property(&document, "og:title"),
+        description: parse_meta_property(&document, "og:description"),
+        image_url: parse_meta_property(&document, "og:image"),
+        avatar_url: parse_profile_pic_url(html),
+        metadata: ReviewMetadata {
+            likes: parse_review_likes(&document),
+            comments: parse_review_comments(&document),
+            status: parse_status_text(&document),
+        },
+    }
+}
+
+pub fn parse_meta_property(document: &Html, property: &str) -> Option<String> {
+    let selector = scraper::Selector::parse(&format!(r#"meta[property="{}"]"#, property)).ok()?;
+    let meta = document.select(&selector).next()?;
+    let content = meta.value().attr("content")?.trim();
+
+    if content.is_empty() {
+        return None;
+    }
+
+    Some(content.to_string())
+}
+
+pub fn build_game_search_url(query: &str) -> Result<String, Error> {
+    let mut url = reqwest::Url::parse("https://backloggd.com/search/games/")?;
+
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("Search URL can't have path segments"))?
+        .pop_if_empty()
+        .push(query.trim())
+        .push("");
+
+    Ok(url.to_string())
+}
+
+pub fn parse_game_search_results(html: &str) -> Vec<GameSearchResult> {
+    let document = Html::parse_document(html);
+    let (Ok(result), Ok(link), Ok(name), Ok(year)) = (
+        scraper::Selector::parse("div.result"),
+        scraper::Selector::parse(r#"a[href^="/games/"]"#),
+        scraper::Selector::parse("h3.game-name"),
+        scraper::Selector::parse("span.game-year"),
+    ) else {
+        return vec![];
+    };
+
+    document
+        .select(&result)
+        .filter_map(|result| {
+            let href = result.select(&link).next()?.value().attr("href")?;
+            let title = result.select(&name).next()?.text().collect::<String>();
+
+            Some(GameSearchResult {
+                title: title.trim().to_string(),
+                year: result
+                    .select(&year)
+                    .next()
+                    .map(|year| year.text().collect::<String>().trim().to_string()),
+                url: format!("https://backloggd.com{}", href),
+            })
+        })
+        .collect()
+}
+
+/// Picks the game to show without asking, when there's only one result or exactly one result
+/// has the searched title.
+pub fn select_exact_match<'a>(
+    results: &'a [GameSearchResult],
+    title: &str,
+) -> Option<&'a GameSearchResult> {
+    if let [result] = results {
+        return Some(result);
+    }
+
+    let mut matches = results
+        .iter()
+        .filter(|result| result.title.eq_ignore_ascii_case(title.trim()));
+
+    match (matches.next(), matches.next()) {
+        (Some(result), None) => Some(result),
+        _ => None,
+    }
+}
+
+pub fn parse_game_details(html: &str, game_url: &str) -> Option<GameDetails> {
+    let document = Html::parse_document(html);
+
+    let title = select_text(&document, "#title h1")?;
+
+    Some(GameDetails {
+        title,
+        url: game_url.to_string(),
+        cover_url: parse_meta_property(&document, "og:image"),
+        release_date: select_text(&document, "div.game-subtitle a"),
+        platforms: select_all_text(&document, "a.game-page-platform"),
+        average_rating: select_text(&document, "#score h1").and_then(|score| score.parse().ok()),
+        rating_distribution: parse_rating_distribution(&document),
+        plays: parse_game_plays(&document),
+    })
+}
+
+pub fn parse_rating_distribution(document: &Html) -> Vec<u32> {
+    let Ok(bar) = scraper::Selector::parse("#rating-distribution .st-bar") else {
+        return vec![];
+    };
+
+    // Each bar's tooltip reads like "1,650 | ★★★½"
+    document
+        .select(&bar)
+        .filter_map(|bar| {
+            let tooltip = bar.value().attr("data-tippy-content")?;
+            let count = tooltip.split('|').next()?.trim().replace(',', "");
+            count.parse().ok()
+        })
+        .collect()
+}
+
+pub fn parse_game_plays(document: &Html) -> GamePlays {
+    let mut plays = GamePlays {
+        played: None,
+        playing: None,
+        backlogs: None,
+        wishlists: None,
+    };
+
+    let (Ok(sidecard), Ok(count), Ok(label)) = (
+        scraper::Selector::parse("div.game-page-sidecard"),
+        scraper::Selector::parse("p.play-count"),
+        scraper::Selector::parse("p.play-label"),
+    ) else {
+        return plays;
+    };
+
+    for card in document.select(&sidecard) {
+        let (Some(count), Some(label)) = (card.select(&count).next(), card.select(&label).next())
+        else {
+            continue;
+        };
+
+        let count = Some(count.text().collect::<String>().trim().to_string());
+
+        match label.text().collect::<String>().trim() {
+            "Played" => plays.played = count,
+            "Playing" => plays.playing = count,
+            "Backlogs" => plays.backlogs = count,
+            "Wishlists" => plays.wishlists = count,
+            _ => {}
         }
+    }
+
+    plays
+}
+
+pub fn parse_user_profile(html: &str, username: &str, profile_url: &str) -> UserProfile {
+    let document = Html::parse_document(html);
+    let mut profile = UserProfile {
+        username: username.to_string(),
+        url: profile_url.to_string(),
+        avatar_url: parse_profile_pic_url(html),
+        bio: select_text(&document, "#bio-body"),
+        games_played: None,
+        backlog: None,
+        reviews: None,
+        favorite_games: parse_game_cover_titles(&document, "#profile-favorites"),
+        playing: parse_game_cover_titles(&document, "#profile-playing"),
+    };
+
+    let (Ok(stat), Ok(value), Ok(label)) = (
+        scraper::Selector::parse("div.profile-stat"),
+        scraper::Selector::parse("h1"),
+        scraper::Selector::parse("h4"),
+    ) else {
+        return profile;
+    };
+
+    for stat in document.select(&stat) {
+        let (Some(value), Some(label)) = (stat.select(&value).next(), stat.select(&label).next())
+        else {
+            continue;
+        };
+
+        let value = Some(value.text().collect::<String>().trim().to_string());
+
+        match label.text().collect::<String>().trim() {
+            "Total Games Played" => profile.games_played = value,
+            "Backloggd" => profile.backlog = value,
+            "Reviews" => profile.reviews = value,
+            _ => {}
+        }
+    }
+
+    profile
+}
+
+/// None when the feed was returned and only its content can tell whether it's a feed.
+pub fn feed_status_from_code(status: StatusCode) -> Option<FeedStatus> {
+    match status {
+        StatusCode::OK => None,
+        StatusCode::NOT_FOUND | StatusCode::GONE => Some(FeedStatus::NotFound),
+        StatusCode::TOO_MANY_REQUESTS => Some(FeedStatus::RateLimited),
+        _ => Some(FeedStatus::Unavailable),
+    }
+}
+
+/// Only sends the validators we have, servers may support ETag, Last-Modified, both or neither.
+pub fn build_conditional_headers(request: &RssRequest) -> Vec<(HeaderName, &str)> {
+    let mut headers = vec![];
+
+    if let Some(etag) = request.etag.as_deref().filter(|etag| !etag.is_empty()) {
+        headers.push((IF_NONE_MATCH, etag));
+    }
+
+    if let Some(last_modified) = request
+        .last_modified
+        .as_deref()
+        .filter(|last_modified| !last_modified.is_empty())
+    {
+        headers.push((IF_MODIFIED_SINCE, last_modified));
+    }
+
+    headers
+}
+
+pub fn read_validator(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    let value = headers.get(name)?.to_str().ok()?.trim();
+
+    if value.is_empty() {
+        return None;
+    }
+
+    Some(value.to_string())
+}
+
+pub fn build_profile_url(username: &str) -> String {
+    format!("https://backloggd.com/u/{}/", username)
+}
+
+/// Feeds live under the reviewer's profile, so the profile URL is the feed URL without
+/// `reviews/rss/`.
+pub fn profile_url_from_feed_url(feed_url: &str) -> Option<String> {
+    let username = feed_url
+        .strip_prefix("https://backloggd.com/u/")
+        .or_else(|| feed_url.strip_prefix("https://www.backloggd.com/u/"))?
+        .trim_end_matches('/')
+        .strip_suffix("/reviews/rss")?;
+
+    if username.is_empty() || username.contains('/') {
+        return None;
+    }
+
+    Some(build_profile_url(username))
+}
+
+/// Falls back to the reviewer's name when the feed URL isn't a Backloggd feed.
+pub fn find_profile_url(feed_url: &str, items: &[RssItem]) -> Option<String> {
+    profile_url_from_feed_url(feed_url)
+        .or_else(|| items.first().map(|item| build_profile_url(&item.reviewer)))
+}
+
+pub fn build_game_log_url(username: &str, game_url: &str) -> Option<String> {
+    let slug = game_url
+        .strip_prefix("https://backloggd.com/games/")?
+        .trim_end_matches('/');
+
+    if slug.is_empty() || slug.contains('/') {
+        return None;
+    }
+
+    Some(format!(
+        "https://backloggd.com/u/{}/logs/{}/",
+        username, slug
+    ))
+}
+
+pub fn parse_game_log(html: &str, log_url: &str) -> Option<GameLog> {
+    let document = Html::parse_document(html);
+
+    let title = select_text(&document, "#log-header h1.game-title")?;
+
+    let review_url = scraper::Selector::parse("#log-header a.review-link")
+        .ok()
+        .and_then(|link| {
+            let href = document.select(&link).next()?.value().attr("href")?;
+            Some(format!("https://backloggd.com{}", href))
+        });
+
+    Some(GameLog {
+        title,
+        url: log_url.to_string(),
+        rating: parse_star_rating(&document),
+        status: parse_status_text(&document),
+        review_url,
+    })
+}
+
+/// Stars are drawn as a filled layer clipped to the rating, e.g. width:90% for four and a half.
+pub fn parse_star_rating(document: &Html) -> Option<i8> {
+    let stars = scraper::Selector::parse("div.stars-top").ok()?;
+    let style = document.select(&stars).next()?.value().attr("style")?;
+
+    let percent: f32 = style
+        .split(';')
+        .find_map(|rule| rule.trim().strip_prefix("width:"))?
+        .trim()
+        .trim_end_matches('%')
+        .parse()
+        .ok()?;
+
+    let rating = (percent / 10.0).round() as i8;
+
+    if rating <= 0 {
+        return None;
+    }
+
+    Some(rating.min(10))
+}
 
-        return Ok(false);
+/// Returns the titles of the game covers inside the given section, taken from the cover's alt text.
+fn parse_game_cover_titles(document: &Html, section: &str) -> Vec<String> {
+    let Ok(cover) = scraper::Selector::parse(&format!("{} div.game-cover img", section)) else {
+        return vec![];
+    };
+
+    document
+        .select(&cover)
+        .filter_map(|img| img.value().attr("alt"))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .collect()
+}
+
+fn select_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = scraper::Selector::parse(selector).ok()?;
+    let text = document
+        .select(&selector)
+        .next()?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+
+    if text.is_empty() {
+        return None;
     }
+
+    Some(text)
+}
+
+fn select_all_text(document: &Html, selector: &str) -> Vec<String> {
+    let Ok(selector) = scraper::Selector::parse(selector) else {
+        return vec![];
+    };
+
+    document
+        .select(&selector)
+        .map(|element| element.text().collect::<String>().trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect()
 }
 
 pub fn parse_review_metadata(html: &str) -> ReviewMetadata {
@@ -163,10 +735,488 @@ pub fn parse_status_text(document: &Html) -> Option<String> {
 
 pub fn parse_profile_pic_url(html: &str) -> Option<String> {
     let document = Html::parse_document(html);
-    let selector = scraper::Selector::parse("div.avatar").ok()?;
+    // The img isn't always the avatar's first child, whitespace or wrappers can come before it
+    let selector = scraper::Selector::parse("div.avatar img").ok()?;
+
+    let img = document.select(&selector).next()?.value();
+
+    // Lazy-loaded avatars keep the real URL in data-src and a placeholder in src
+    let url = ["data-src", "src"]
+        .iter()
+        .filter_map(|attribute| img.attr(attribute))
+        .map(str::trim)
+        .find(|url| !url.is_empty() && !url.starts_with("data:"))?;
+
+    match url.strip_prefix("//") {
+        Some(url) => Some(format!("https://{}", url)),
+        None => Some(url.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REVIEW_HTML: &str = include_str!("fixtures/review.html");
+    const GAME_HTML: &str = include_str!("fixtures/game.html");
+    const USER_HTML: &str = include_str!("fixtures/user.html");
+    const GAME_SEARCH_HTML: &str = include_str!("fixtures/game_search.html");
+    const GAME_LOG_HTML: &str = include_str!("fixtures/game_log.html");
+    const GAME_LOG_UNREVIEWED_HTML: &str = include_str!("fixtures/game_log_unreviewed.html");
+    const AVATAR_WHITESPACE_HTML: &str = include_str!("fixtures/avatar_whitespace.html");
+    const AVATAR_LAZY_HTML: &str = include_str!("fixtures/avatar_lazy.html");
+    const AVATAR_PROTOCOL_RELATIVE_HTML: &str =
+        include_str!("fixtures/avatar_protocol_relative.html");
+
+    #[test]
+    fn build_game_search_url_encodes_query() {
+        let actual = build_game_search_url(" hollow knight/silksong ").unwrap();
+
+        assert_eq!(
+            actual,
+            "https://backloggd.com/search/games/hollow%20knight%2Fsilksong/"
+        );
+    }
+
+    #[test]
+    fn parse_game_search_results_returns_each_result() {
+        let actual = parse_game_search_results(GAME_SEARCH_HTML);
+
+        assert_eq!(
+            actual,
+            vec![
+                GameSearchResult {
+                    title: "Hollow Knight".to_string(),
+                    year: Some("2017".to_string()),
+                    url: "https://backloggd.com/games/hollow-knight/".to_string(),
+                },
+                GameSearchResult {
+                    title: "Hollow Knight: Silksong".to_string(),
+                    year: Some("2025".to_string()),
+                    url: "https://backloggd.com/games/hollow-knight-silksong/".to_string(),
+                },
+                GameSearchResult {
+                    title: "Hollow Knight: Voidheart Edition".to_string(),
+                    year: None,
+                    url: "https://backloggd.com/games/hollow-knight-voidheart-edition/".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_game_search_results_returns_empty_when_no_results() {
+        let actual = parse_game_search_results("<html><body><p>No results</p></body></html>");
+
+        assert!(actual.is_empty());
+    }
+
+    fn build_search_result(title: &str, year: Option<&str>) -> GameSearchResult {
+        GameSearchResult {
+            title: title.to_string(),
+            year: year.map(str::to_string),
+            url: format!("https://backloggd.com/games/{}/", title.to_lowercase()),
+        }
+    }
+
+    #[test]
+    fn select_exact_match_returns_only_result() {
+        let results = vec![build_search_result("Celeste", Some("2018"))];
+
+        let actual = select_exact_match(&results, "celest");
+
+        assert_eq!(actual, Some(&results[0]));
+    }
+
+    #[test]
+    fn select_exact_match_returns_result_with_same_title() {
+        let results = vec![
+            build_search_result("Hollow Knight", Some("2017")),
+            build_search_result("Hollow Knight: Silksong", Some("2025")),
+        ];
+
+        let actual = select_exact_match(&results, " hollow knight ");
+
+        assert_eq!(actual, Some(&results[0]));
+    }
 
-    let div = document.select(&selector).next()?;
+    #[test]
+    fn select_exact_match_returns_none_when_ambiguous() {
+        let results = vec![
+            build_search_result("Doom", Some("1993")),
+            build_search_result("Doom", Some("2016")),
+            build_search_result("Doom Eternal", Some("2020")),
+        ];
 
-    let img = div.first_child()?;
-    return Some(img.value().as_element()?.attr("src")?.to_string());
+        assert_eq!(select_exact_match(&results, "doom"), None);
+        assert_eq!(select_exact_match(&results, "do"), None);
+    }
+
+    #[test]
+    fn parse_game_details_returns_game() {
+        let actual =
+            parse_game_details(GAME_HTML, "https://backloggd.com/games/hollow-knight/").unwrap();
+
+        assert_eq!(actual.title, "Hollow Knight");
+        assert_eq!(actual.url, "https://backloggd.com/games/hollow-knight/");
+        assert_eq!(
+            actual.cover_url.as_deref(),
+            Some("https://images.igdb.com/igdb/image/upload/t_cover_big/co93cr.jpg")
+        );
+        assert_eq!(actual.release_date.as_deref(), Some("Feb 24, 2017"));
+        assert_eq!(
+            actual.platforms,
+            vec!["Windows PC", "Nintendo Switch", "PlayStation 4"]
+        );
+        assert_eq!(actual.average_rating, Some(4.4));
+        assert_eq!(
+            actual.rating_distribution,
+            vec![120, 85, 140, 260, 410, 980, 1650, 4320, 6780, 12940]
+        );
+        assert_eq!(actual.plays.played.as_deref(), Some("61K"));
+        assert_eq!(actual.plays.playing.as_deref(), Some("2.4K"));
+        assert_eq!(actual.plays.backlogs.as_deref(), Some("19K"));
+        assert_eq!(actual.plays.wishlists.as_deref(), Some("8.1K"));
+    }
+
+    #[test]
+    fn parse_user_profile_returns_profile() {
+        let actual =
+            parse_user_profile(USER_HTML, "bodycakes", "https://backloggd.com/u/bodycakes/");
+
+        assert_eq!(actual.username, "bodycakes");
+        assert_eq!(actual.url, "https://backloggd.com/u/bodycakes/");
+        assert_eq!(
+            actual.avatar_url.as_deref(),
+            Some("https://backloggd-s3.b-cdn.net/avatars/bodycakes.jpg")
+        );
+        assert_eq!(
+            actual.bio.as_deref(),
+            Some("Mostly metroidvanias and the occasional JRPG.")
+        );
+        assert_eq!(actual.games_played.as_deref(), Some("312"));
+        assert_eq!(actual.backlog.as_deref(), Some("85"));
+        assert_eq!(actual.reviews.as_deref(), Some("1,048"));
+        assert_eq!(
+            actual.favorite_games,
+            vec!["Hollow Knight", "Celeste", "Outer Wilds"]
+        );
+        assert_eq!(
+            actual.playing,
+            vec!["Hollow Knight: Silksong", "Persona 5 Royal"]
+        );
+    }
+
+    #[test]
+    fn parse_user_profile_returns_empty_sections_when_missing() {
+        let actual = parse_user_profile(
+            "<html><body><h3 class=\"main-header\">newuser</h3></body></html>",
+            "newuser",
+            "https://backloggd.com/u/newuser/",
+        );
+
+        assert_eq!(actual.avatar_url, None);
+        assert_eq!(actual.bio, None);
+        assert_eq!(actual.games_played, None);
+        assert!(actual.favorite_games.is_empty());
+        assert!(actual.playing.is_empty());
+    }
+
+    fn build_rss_request(etag: Option<&str>, last_modified: Option<&str>) -> RssRequest {
+        RssRequest {
+            url: "https://backloggd.com/u/bodycakes/reviews/rss/".to_string(),
+            etag: etag.map(str::to_string),
+            last_modified: last_modified.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn build_conditional_headers_sends_both_validators() {
+        let request = build_rss_request(Some("\"abc123\""), Some("Sat, 01 Mar 2025 12:00:00 GMT"));
+
+        assert_eq!(
+            build_conditional_headers(&request),
+            vec![
+                (IF_NONE_MATCH, "\"abc123\""),
+                (IF_MODIFIED_SINCE, "Sat, 01 Mar 2025 12:00:00 GMT"),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_conditional_headers_sends_only_known_validators() {
+        let etag_only = build_rss_request(Some("\"abc123\""), None);
+        let last_modified_only = build_rss_request(Some(""), Some("Sat, 01 Mar 2025 12:00:00 GMT"));
+
+        assert_eq!(
+            build_conditional_headers(&etag_only),
+            vec![(IF_NONE_MATCH, "\"abc123\"")]
+        );
+        assert_eq!(
+            build_conditional_headers(&last_modified_only),
+            vec![(IF_MODIFIED_SINCE, "Sat, 01 Mar 2025 12:00:00 GMT")]
+        );
+    }
+
+    #[test]
+    fn build_conditional_headers_sends_nothing_without_validators() {
+        assert!(build_conditional_headers(&build_rss_request(None, None)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_rss_feed_content_keeps_validators_when_not_modified() {
+        let (url, requests) = http::tests::start_stub_server(vec![Some(
+            "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n",
+        )])
+        .await;
+        let scraper = ReqwestScraper::new(http::tests::build_config()).unwrap();
+        let request = RssRequest {
+            url,
+            ..build_rss_request(Some("\"abc123\""), Some("Sat, 01 Mar 2025 12:00:00 GMT"))
+        };
+
+        let actual = scraper.get_rss_feed_content(&request).await.unwrap();
+
+        assert_eq!(actual.content, None);
+        assert_eq!(actual.etag, request.etag);
+        assert_eq!(actual.last_modified, request.last_modified);
+        assert!(requests.lock().unwrap()[0]
+            .to_lowercase()
+            .contains("if-none-match: \"abc123\""));
+    }
+
+    #[test]
+    fn read_validator_ignores_missing_and_empty_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, "W/\"abc123\"".parse().unwrap());
+        headers.insert(LAST_MODIFIED, " ".parse().unwrap());
+
+        assert_eq!(
+            read_validator(&headers, ETAG).as_deref(),
+            Some("W/\"abc123\"")
+        );
+        assert_eq!(read_validator(&headers, LAST_MODIFIED), None);
+        assert_eq!(read_validator(&HeaderMap::new(), ETAG), None);
+    }
+
+    #[test]
+    fn feed_status_from_code_tells_missing_feeds_from_errors() {
+        assert_eq!(feed_status_from_code(StatusCode::OK), None);
+        assert_eq!(
+            feed_status_from_code(StatusCode::NOT_FOUND),
+            Some(FeedStatus::NotFound)
+        );
+        assert_eq!(
+            feed_status_from_code(StatusCode::TOO_MANY_REQUESTS),
+            Some(FeedStatus::RateLimited)
+        );
+        assert_eq!(
+            feed_status_from_code(StatusCode::SERVICE_UNAVAILABLE),
+            Some(FeedStatus::Unavailable)
+        );
+        assert_eq!(
+            feed_status_from_code(StatusCode::METHOD_NOT_ALLOWED),
+            Some(FeedStatus::Unavailable)
+        );
+    }
+
+    #[test]
+    fn profile_url_from_feed_url_returns_profile_url() {
+        assert_eq!(
+            profile_url_from_feed_url("https://backloggd.com/u/bodycakes/reviews/rss/").as_deref(),
+            Some("https://backloggd.com/u/bodycakes/")
+        );
+        assert_eq!(
+            profile_url_from_feed_url("https://www.backloggd.com/u/bodycakes/reviews/rss")
+                .as_deref(),
+            Some("https://backloggd.com/u/bodycakes/")
+        );
+    }
+
+    #[test]
+    fn profile_url_from_feed_url_returns_none_when_not_feed_url() {
+        assert_eq!(
+            profile_url_from_feed_url("https://backloggd.com/u/bodycakes/"),
+            None
+        );
+        assert_eq!(
+            profile_url_from_feed_url("https://example.com/u/bodycakes/reviews/rss/"),
+            None
+        );
+        assert_eq!(
+            profile_url_from_feed_url("https://backloggd.com/u//reviews/rss/"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_profile_pic_url_returns_avatar() {
+        assert_eq!(
+            parse_profile_pic_url(USER_HTML).as_deref(),
+            Some("https://backloggd-s3.b-cdn.net/avatars/bodycakes.jpg")
+        );
+    }
+
+    #[test]
+    fn parse_profile_pic_url_skips_whitespace_before_img() {
+        assert_eq!(
+            parse_profile_pic_url(AVATAR_WHITESPACE_HTML).as_deref(),
+            Some("https://backloggd-s3.b-cdn.net/avatars/bodycakes.jpg")
+        );
+    }
+
+    #[test]
+    fn parse_profile_pic_url_prefers_lazy_loaded_src() {
+        assert_eq!(
+            parse_profile_pic_url(AVATAR_LAZY_HTML).as_deref(),
+            Some("https://backloggd-s3.b-cdn.net/avatars/bodycakes.jpg")
+        );
+    }
+
+    #[test]
+    fn parse_profile_pic_url_adds_scheme_to_protocol_relative_src() {
+        assert_eq!(
+            parse_profile_pic_url(AVATAR_PROTOCOL_RELATIVE_HTML).as_deref(),
+            Some("https://backloggd-s3.b-cdn.net/avatars/bodycakes.jpg")
+        );
+    }
+
+    #[test]
+    fn parse_profile_pic_url_returns_none_without_avatar() {
+        assert_eq!(parse_profile_pic_url(GAME_HTML), None);
+    }
+
+    #[test]
+    fn build_game_log_url_returns_user_log_for_game() {
+        let actual = build_game_log_url("bodycakes", "https://backloggd.com/games/hollow-knight/");
+
+        assert_eq!(
+            actual.as_deref(),
+            Some("https://backloggd.com/u/bodycakes/logs/hollow-knight/")
+        );
+    }
+
+    #[test]
+    fn build_game_log_url_returns_none_when_not_game_url() {
+        assert_eq!(
+            build_game_log_url("bodycakes", "https://backloggd.com/u/bodycakes/"),
+            None
+        );
+        assert_eq!(
+            build_game_log_url("bodycakes", "https://backloggd.com/games/"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_game_log_returns_reviewed_log() {
+        let actual = parse_game_log(
+            GAME_LOG_HTML,
+            "https://backloggd.com/u/bodycakes/logs/hollow-knight/",
+        )
+        .unwrap();
+
+        assert_eq!(actual.title, "Hollow Knight");
+        assert_eq!(
+            actual.url,
+            "https://backloggd.com/u/bodycakes/logs/hollow-knight/"
+        );
+        assert_eq!(actual.rating, Some(9));
+        assert_eq!(actual.status.as_deref(), Some("Completed"));
+        assert_eq!(
+            actual.review_url.as_deref(),
+            Some("https://backloggd.com/u/bodycakes/review/123456/")
+        );
+    }
+
+    #[test]
+    fn parse_game_log_returns_unreviewed_log() {
+        let actual = parse_game_log(
+            GAME_LOG_UNREVIEWED_HTML,
+            "https://backloggd.com/u/bodycakes/logs/celeste/",
+        )
+        .unwrap();
+
+        assert_eq!(actual.title, "Celeste");
+        assert_eq!(actual.rating, None);
+        assert_eq!(actual.status.as_deref(), Some("Shelved"));
+        assert_eq!(actual.review_url, None);
+    }
+
+    #[test]
+    fn parse_game_details_returns_none_when_not_game_page() {
+        let actual = parse_game_details(USER_HTML, "https://backloggd.com/u/bodycakes/");
+
+        assert!(actual.is_none());
+    }
+
+    #[test]
+    fn parse_page_preview_returns_review_details() {
+        let actual = parse_page_preview(REVIEW_HTML);
+
+        assert_eq!(
+            actual.title.as_deref(),
+            Some("bodycakes's review of Hollow Knight")
+        );
+        assert_eq!(
+            actual.description.as_deref(),
+            Some("Tight combat, a gorgeous world and a map you'll want to get lost in.")
+        );
+        assert_eq!(
+            actual.image_url.as_deref(),
+            Some("https://images.igdb.com/igdb/image/upload/t_cover_big/co93cr.jpg")
+        );
+        assert_eq!(
+            actual.avatar_url.as_deref(),
+            Some("https://backloggd-s3.b-cdn.net/avatars/bodycakes.jpg")
+        );
+        assert_eq!(actual.metadata.likes.as_deref(), Some("12"));
+        assert_eq!(actual.metadata.comments.as_deref(), Some("3"));
+        assert_eq!(actual.metadata.status.as_deref(), Some("Completed"));
+    }
+
+    #[test]
+    fn parse_page_preview_returns_game_details() {
+        let actual = parse_page_preview(GAME_HTML);
+
+        assert_eq!(actual.title.as_deref(), Some("Hollow Knight (2017)"));
+        assert!(actual
+            .description
+            .unwrap()
+            .starts_with("Forge your own path in Hollow Knight!"));
+        assert_eq!(
+            actual.image_url.as_deref(),
+            Some("https://images.igdb.com/igdb/image/upload/t_cover_big/co93cr.jpg")
+        );
+        assert_eq!(actual.avatar_url, None);
+        assert_eq!(actual.metadata.likes, None);
+        assert_eq!(actual.metadata.comments, None);
+        assert_eq!(actual.metadata.status, None);
+    }
+
+    #[test]
+    fn parse_page_preview_returns_profile_details() {
+        let actual = parse_page_preview(USER_HTML);
+
+        assert_eq!(actual.title.as_deref(), Some("bodycakes's Profile"));
+        assert_eq!(
+            actual.description.as_deref(),
+            Some("Mostly metroidvanias and the occasional JRPG.")
+        );
+        assert_eq!(actual.image_url, None);
+        assert_eq!(
+            actual.avatar_url.as_deref(),
+            Some("https://backloggd-s3.b-cdn.net/avatars/bodycakes.jpg")
+        );
+    }
+
+    #[test]
+    fn parse_page_preview_returns_none_when_page_has_no_metadata() {
+        let actual = parse_page_preview("<html><body><p>Not found</p></body></html>");
+
+        assert_eq!(actual.title, None);
+        assert_eq!(actual.description, None);
+        assert_eq!(actual.image_url, None);
+        assert_eq!(actual.avatar_url, None);
+    }
 }
@@ -0,0 +1,152 @@
+use anyhow::Error;
+use chrono::{NaiveDate, NaiveDateTime};
+use poise::serenity_prelude::{Color, CreateEmbed, CreateEmbedFooter};
+
+use super::converter;
+use super::models::ReviewSearchResult;
+
+pub const SEARCH_PAGE_SIZE: usize = 5;
+// Results are fetched once and paged through in memory
+pub const MAX_SEARCH_RESULTS: u32 = 50;
+// Discord rejects embed titles longer than 256 characters, this leaves room for the rest of it
+const MAX_TITLE_QUERY_LENGTH: usize = 200;
+
+/// Turns what the user typed into an FTS5 query matching reviews containing every word. Each word
+/// is quoted so characters like - or " can't be read as query syntax, and the last word matches
+/// as a prefix. Returns None when there are no words to search for.
+pub fn to_fts_query(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+
+    if words.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}*", words.join(" ")))
+}
+
+/// Reads a YYYY-MM-DD date as the start of that day.
+pub fn parse_date_filter(date: &str) -> Result<NaiveDateTime, Error> {
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")?;
+    Ok(date.and_time(chrono::NaiveTime::MIN))
+}
+
+/// Converts a rating in stars, e.g. 3.5, to the half stars ratings are stored as.
+pub fn stars_to_rating(stars: f32) -> i8 {
+    (stars * 2.0).round() as i8
+}
+
+pub fn page_count(results: &[ReviewSearchResult]) -> usize {
+    results.len().div_ceil(SEARCH_PAGE_SIZE).max(1)
+}
+
+pub fn build_search_title(query: &str) -> String {
+    let query: String = query.trim().chars().take(MAX_TITLE_QUERY_LENGTH).collect();
+
+    format!("Reviews matching \"{}\"", query)
+}
+
+pub fn build_search_embed(query: &str, results: &[ReviewSearchResult], page: usize) -> CreateEmbed {
+    let embed = CreateEmbed::new()
+        .color(Color::from_rgb(252, 99, 153))
+        .title(build_search_title(query));
+
+    if results.is_empty() {
+        return embed.description("No reviews posted in this server match your search");
+    }
+
+    let footer = CreateEmbedFooter::new(format!(
+        "Page {} of {} • {} results",
+        page + 1,
+        page_count(results),
+        results.len()
+    ));
+
+    let fields = results
+        .iter()
+        .skip(page * SEARCH_PAGE_SIZE)
+        .take(SEARCH_PAGE_SIZE)
+        .map(|result| {
+            let review = &result.review;
+            let mut name = format!("{} by {}", review.title, review.reviewer);
+            let stars = converter::rating_to_stars(review.user_rating);
+
+            if !stars.is_empty() {
+                name.push_str(&format!(" • {}", stars));
+            }
+
+            let value = format!(
+                "{}\n[Read review]({}) • {}",
+                result.snippet,
+                review.link,
+                review.pub_date.format("%-d %b %Y")
+            );
+
+            (name, value, false)
+        });
+
+    embed.fields(fields).footer(footer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{build_test_review, StoredReview};
+
+    fn build_results(count: usize) -> Vec<ReviewSearchResult> {
+        (0..count)
+            .map(|index| ReviewSearchResult {
+                review: StoredReview {
+                    guid: index.to_string(),
+                    link: format!("https://backloggd.com/u/username1/review/{}/", index),
+                    ..build_test_review(&format!("Game {}", index), 8)
+                },
+                snippet: "**Description**".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn to_fts_query_quotes_each_word() {
+        assert_eq!(
+            to_fts_query(" great  combat "),
+            Some("\"great\" \"combat\"*".to_string())
+        );
+        assert_eq!(
+            to_fts_query("say \"hi\" -x"),
+            Some("\"say\" \"\"\"hi\"\"\" \"-x\"*".to_string())
+        );
+        assert_eq!(to_fts_query("   "), None);
+    }
+
+    #[test]
+    fn parse_date_filter_reads_iso_dates() {
+        assert_eq!(
+            parse_date_filter("2025-03-01").unwrap().to_string(),
+            "2025-03-01 00:00:00"
+        );
+        assert!(parse_date_filter("01/03/2025").is_err());
+    }
+
+    #[test]
+    fn stars_to_rating_rounds_to_half_stars() {
+        assert_eq!(stars_to_rating(3.5), 7);
+        assert_eq!(stars_to_rating(4.2), 8);
+        assert_eq!(stars_to_rating(5.0), 10);
+    }
+
+    #[test]
+    fn page_count_rounds_up() {
+        assert_eq!(page_count(&build_results(0)), 1);
+        assert_eq!(page_count(&build_results(5)), 1);
+        assert_eq!(page_count(&build_results(6)), 2);
+    }
+
+    #[test]
+    fn build_search_title_truncates_long_queries() {
+        assert_eq!(build_search_title(" zelda "), "Reviews matching \"zelda\"");
+        assert!(build_search_title(&"a".repeat(6000)).chars().count() <= 256);
+    }
+}
@@ -0,0 +1,437 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime};
+use poise::serenity_prelude::{Color, CreateEmbed};
+use std::collections::HashMap;
+
+use super::converter;
+use super::models::StoredReview;
+
+// Discord rejects embed field values longer than 1024 characters, ten lines stays well under it
+const LEADERBOARD_SIZE: usize = 10;
+// Stops a single five star review from topping the average rating board
+const MIN_RATINGS_FOR_AVERAGE: usize = 3;
+const STATS_MONTHS: u32 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsPeriod {
+    Month,
+    Year,
+    AllTime,
+}
+
+impl StatsPeriod {
+    pub fn start(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let date = match self {
+            StatsPeriod::Month => now.date().with_day(1),
+            StatsPeriod::Year => NaiveDate::from_ymd_opt(now.year(), 1, 1),
+            StatsPeriod::AllTime => None,
+        };
+
+        date.and_then(|date| date.and_hms_opt(0, 0, 0))
+            .unwrap_or(DateTime::UNIX_EPOCH.naive_utc())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatsPeriod::Month => "This month",
+            StatsPeriod::Year => "This year",
+            StatsPeriod::AllTime => "All time",
+        }
+    }
+}
+
+/// Returns reviewers with the most reviews first, ties broken by name.
+pub fn rank_by_review_count(reviews: &[StoredReview]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for review in reviews {
+        *counts.entry(&review.reviewer).or_default() += 1;
+    }
+
+    let mut ranking: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(reviewer, count)| (reviewer.to_string(), count))
+        .collect();
+
+    ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranking.truncate(LEADERBOARD_SIZE);
+
+    ranking
+}
+
+/// Returns reviewers with the highest average rating in stars first. Unrated reviews don't count
+/// and reviewers need a few ratings to be ranked.
+pub fn rank_by_average_rating(reviews: &[StoredReview]) -> Vec<(String, f32)> {
+    let mut ratings: HashMap<&str, Vec<i8>> = HashMap::new();
+
+    for review in reviews.iter().filter(|review| review.user_rating > 0) {
+        ratings
+            .entry(&review.reviewer)
+            .or_default()
+            .push(review.user_rating);
+    }
+
+    let mut ranking: Vec<(String, f32)> = ratings
+        .into_iter()
+        .filter(|(_, ratings)| ratings.len() >= MIN_RATINGS_FOR_AVERAGE)
+        .map(|(reviewer, ratings)| (reviewer.to_string(), average_stars(&ratings)))
+        .collect();
+
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranking.truncate(LEADERBOARD_SIZE);
+
+    ranking
+}
+
+pub fn most_liked_review(reviews: &[StoredReview]) -> Option<&StoredReview> {
+    reviews
+        .iter()
+        .filter(|review| review.likes.unwrap_or(0) > 0)
+        .max_by_key(|review| (review.likes, review.pub_date))
+}
+
+/// Returns the average rating in stars, or None when none of the reviews are rated.
+pub fn average_rating(reviews: &[StoredReview]) -> Option<f32> {
+    let ratings: Vec<i8> = reviews
+        .iter()
+        .map(|review| review.user_rating)
+        .filter(|rating| *rating > 0)
+        .collect();
+
+    if ratings.is_empty() {
+        return None;
+    }
+
+    Some(average_stars(&ratings))
+}
+
+/// Counts the ratings for each half star from ½ to ★★★★★.
+pub fn rating_counts(reviews: &[StoredReview]) -> [u32; 10] {
+    let mut counts = [0; 10];
+
+    for review in reviews {
+        if (1..=10).contains(&review.user_rating) {
+            counts[(review.user_rating - 1) as usize] += 1;
+        }
+    }
+
+    counts
+}
+
+/// Counts the reviews in each of the last `months` calendar months, oldest first and ending with
+/// the month of `now`.
+pub fn reviews_per_month(
+    reviews: &[StoredReview],
+    now: NaiveDateTime,
+    months: u32,
+) -> Vec<(NaiveDate, u32)> {
+    let current = month_index(now.date());
+
+    (0..months)
+        .rev()
+        .filter_map(|months_ago| {
+            let index = current - months_ago as i32;
+            let month =
+                NaiveDate::from_ymd_opt(index.div_euclid(12), index.rem_euclid(12) as u32 + 1, 1)?;
+            let count = reviews
+                .iter()
+                .filter(|review| month_index(review.pub_date.date()) == index)
+                .count();
+
+            Some((month, count as u32))
+        })
+        .collect()
+}
+
+pub fn build_leaderboard_embed(period: &StatsPeriod, reviews: &[StoredReview]) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .color(Color::from_rgb(252, 99, 153))
+        .title(format!("Server leaderboard • {}", period.name()));
+
+    if reviews.is_empty() {
+        return embed.description("No reviews have been posted yet");
+    }
+
+    let review_counts = rank_by_review_count(reviews)
+        .iter()
+        .enumerate()
+        .map(|(index, (reviewer, count))| format!("{}. {} • {}", index + 1, reviewer, count))
+        .collect::<Vec<String>>();
+
+    embed = embed.field("Most reviews", review_counts.join("\n"), false);
+
+    let average_ratings = rank_by_average_rating(reviews)
+        .iter()
+        .enumerate()
+        .map(|(index, (reviewer, average))| {
+            format!(
+                "{}. {} • {}",
+                index + 1,
+                reviewer,
+                converter::format_average_rating(*average)
+            )
+        })
+        .collect::<Vec<String>>();
+
+    if !average_ratings.is_empty() {
+        embed = embed.field("Highest average rating", average_ratings.join("\n"), false);
+    }
+
+    if let Some(review) = most_liked_review(reviews) {
+        embed = embed.field(
+            "Most liked review",
+            format_liked_review(review, true),
+            false,
+        );
+    }
+
+    embed
+}
+
+pub fn build_stats_embed(
+    username: &str,
+    reviews: &[StoredReview],
+    now: NaiveDateTime,
+) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .color(Color::from_rgb(252, 99, 153))
+        .title(format!("{}'s review stats", username))
+        .url(format!("https://backloggd.com/u/{}/", username));
+
+    if reviews.is_empty() {
+        return embed.description("No reviews have been seen for this user yet");
+    }
+
+    let count_since = |period: StatsPeriod| {
+        let start = period.start(now);
+        reviews
+            .iter()
+            .filter(|review| review.pub_date >= start)
+            .count()
+    };
+
+    embed = embed
+        .field("Reviews", reviews.len().to_string(), true)
+        .field(
+            "This month",
+            count_since(StatsPeriod::Month).to_string(),
+            true,
+        )
+        .field(
+            "This year",
+            count_since(StatsPeriod::Year).to_string(),
+            true,
+        );
+
+    if let Some(average) = average_rating(reviews) {
+        embed = embed.field(
+            "Average rating",
+            converter::format_average_rating(average),
+            true,
+        );
+    }
+
+    if let Some(distribution) = converter::format_rating_distribution(&rating_counts(reviews)) {
+        embed = embed.field("Ratings", distribution, false);
+    }
+
+    let monthly = reviews_per_month(reviews, now, STATS_MONTHS);
+    let counts: Vec<u32> = monthly.iter().map(|(_, count)| *count).collect();
+
+    if let (Some(bars), Some((first, _)), Some((last, _))) = (
+        converter::format_bar_chart(&counts),
+        monthly.first(),
+        monthly.last(),
+    ) {
+        let busiest = monthly
+            .iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(month, count)| {
+                format!("Busiest month: {} with {}", month.format("%b %Y"), count)
+            })
+            .unwrap_or_default();
+
+        embed = embed.field(
+            "Reviews per month",
+            format!(
+                "{} {} {}\n{}",
+                first.format("%b %Y"),
+                bars,
+                last.format("%b %Y"),
+                busiest
+            ),
+            false,
+        );
+    }
+
+    if let Some(review) = most_liked_review(reviews) {
+        embed = embed.field(
+            "Most liked review",
+            format_liked_review(review, false),
+            false,
+        );
+    }
+
+    embed
+}
+
+fn format_liked_review(review: &StoredReview, with_reviewer: bool) -> String {
+    let mut line = format!("[{}]({})", review.title, review.link);
+
+    if with_reviewer {
+        line.push_str(&format!(" by {}", review.reviewer));
+    }
+
+    line.push_str(&format!(" • 🩷 {}", review.likes.unwrap_or(0)));
+
+    line
+}
+
+fn average_stars(ratings: &[i8]) -> f32 {
+    let total: i32 = ratings.iter().map(|rating| *rating as i32).sum();
+
+    // Ratings are stored as half stars
+    total as f32 / ratings.len() as f32 / 2.0
+}
+
+fn month_index(date: NaiveDate) -> i32 {
+    date.year() * 12 + date.month0() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::build_test_review;
+
+    fn build_review(
+        reviewer: &str,
+        rating: i8,
+        date: (i32, u32, u32),
+        likes: Option<i64>,
+    ) -> StoredReview {
+        let (year, month, day) = date;
+
+        StoredReview {
+            guid: format!("{}-{}-{}-{}", reviewer, year, month, day),
+            link: format!("https://backloggd.com/u/{}/review/{}/", reviewer, day),
+            reviewer: reviewer.to_string(),
+            pub_date: NaiveDate::from_ymd_opt(year, month, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            likes,
+            ..build_test_review(&format!("Game {}", day), rating)
+        }
+    }
+
+    fn build_now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, 15)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn stats_period_start_returns_start_of_period() {
+        let now = build_now();
+
+        assert_eq!(
+            StatsPeriod::Month.start(now).to_string(),
+            "2025-03-01 00:00:00"
+        );
+        assert_eq!(
+            StatsPeriod::Year.start(now).to_string(),
+            "2025-01-01 00:00:00"
+        );
+        assert_eq!(
+            StatsPeriod::AllTime.start(now).to_string(),
+            "1970-01-01 00:00:00"
+        );
+    }
+
+    #[test]
+    fn rank_by_review_count_orders_by_count_then_name() {
+        let reviews = vec![
+            build_review("bravo", 8, (2025, 3, 1), None),
+            build_review("alpha", 8, (2025, 3, 2), None),
+            build_review("charlie", 8, (2025, 3, 3), None),
+            build_review("charlie", 8, (2025, 3, 4), None),
+        ];
+
+        let actual = rank_by_review_count(&reviews);
+
+        assert_eq!(
+            actual,
+            vec![
+                ("charlie".to_string(), 2),
+                ("alpha".to_string(), 1),
+                ("bravo".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn rank_by_average_rating_skips_unrated_and_few_ratings() {
+        let reviews = vec![
+            build_review("alpha", 10, (2025, 3, 1), None),
+            build_review("bravo", 6, (2025, 3, 2), None),
+            build_review("bravo", 8, (2025, 3, 3), None),
+            build_review("bravo", 10, (2025, 3, 4), None),
+            build_review("bravo", 0, (2025, 3, 5), None),
+        ];
+
+        let actual = rank_by_average_rating(&reviews);
+
+        assert_eq!(actual, vec![("bravo".to_string(), 4.0)]);
+    }
+
+    #[test]
+    fn most_liked_review_ignores_reviews_without_likes() {
+        let reviews = vec![
+            build_review("alpha", 8, (2025, 3, 1), Some(3)),
+            build_review("bravo", 8, (2025, 3, 2), Some(12)),
+            build_review("charlie", 8, (2025, 3, 3), None),
+        ];
+
+        let actual = most_liked_review(&reviews);
+
+        assert_eq!(actual.map(|review| review.reviewer.as_str()), Some("bravo"));
+        assert!(most_liked_review(&reviews[2..]).is_none());
+    }
+
+    #[test]
+    fn rating_counts_buckets_half_stars() {
+        let reviews = vec![
+            build_review("alpha", 1, (2025, 3, 1), None),
+            build_review("alpha", 10, (2025, 3, 2), None),
+            build_review("alpha", 10, (2025, 3, 3), None),
+            build_review("alpha", 0, (2025, 3, 4), None),
+        ];
+
+        assert_eq!(rating_counts(&reviews), [1, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(average_rating(&reviews), Some(3.5));
+        assert_eq!(average_rating(&reviews[3..]), None);
+    }
+
+    #[test]
+    fn reviews_per_month_counts_each_month_across_years() {
+        let reviews = vec![
+            build_review("alpha", 8, (2024, 12, 20), None),
+            build_review("alpha", 8, (2025, 3, 1), None),
+            build_review("alpha", 8, (2025, 3, 14), None),
+            build_review("alpha", 8, (2024, 6, 1), None),
+        ];
+
+        let actual = reviews_per_month(&reviews, build_now(), 4);
+
+        assert_eq!(
+            actual,
+            vec![
+                (NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(), 1),
+                (NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(), 0),
+                (NaiveDate::from_ymd_opt(2025, 2, 1).unwrap(), 0),
+                (NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(), 2),
+            ]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+// Only one preview reply per channel in this window, so a burst of pasted links can't spam
+pub const UNFURL_COOLDOWN: Duration = Duration::from_secs(30);
+
+// Keep a message full of links from turning into a wall of embeds
+pub const MAX_UNFURLS_PER_MESSAGE: usize = 3;
+
+static BACKLOGGD_LINK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"https?://(?:www\.)?backloggd\.com/(?:u/(?P<user>[A-Za-z0-9_-]{1,16})(?:/review/(?P<review>\d+))?|games/(?P<game>[A-Za-z0-9_-]+))/?(?P<rest>[^\s<>]*)",
+    )
+    .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackloggdLink {
+    Review { url: String, username: String },
+    Game { url: String },
+    User { url: String, username: String },
+}
+
+impl BackloggdLink {
+    pub fn url(&self) -> &str {
+        match self {
+            BackloggdLink::Review { url, .. } => url,
+            BackloggdLink::Game { url } => url,
+            BackloggdLink::User { url, .. } => url,
+        }
+    }
+}
+
+/// Returns the Backloggd review, game and profile links in a message, in the order they appear.
+pub fn find_backloggd_links(content: &str) -> Vec<BackloggdLink> {
+    let mut links: Vec<BackloggdLink> = vec![];
+
+    for captures in BACKLOGGD_LINK_REGEX.captures_iter(content) {
+        // Sub-pages like a user's game list or a game's reviews don't have a useful preview.
+        // Punctuation straight after a link is part of the sentence, not the URL.
+        if !captures["rest"]
+            .trim_end_matches(['.', ',', '!', '?', ')'])
+            .is_empty()
+        {
+            continue;
+        }
+
+        let link = match (
+            captures.name("user"),
+            captures.name("review"),
+            captures.name("game"),
+        ) {
+            (Some(user), Some(review), _) => BackloggdLink::Review {
+                url: format!(
+                    "https://backloggd.com/u/{}/review/{}/",
+                    user.as_str(),
+                    review.as_str()
+                ),
+                username: user.as_str().to_string(),
+            },
+            (Some(user), None, _) => BackloggdLink::User {
+                url: format!("https://backloggd.com/u/{}/", user.as_str()),
+                username: user.as_str().to_string(),
+            },
+            (None, _, Some(game)) => BackloggdLink::Game {
+                url: format!("https://backloggd.com/games/{}/", game.as_str()),
+            },
+            _ => continue,
+        };
+
+        if !links.contains(&link) {
+            links.push(link);
+        }
+    }
+
+    links.truncate(MAX_UNFURLS_PER_MESSAGE);
+
+    links
+}
+
+pub fn is_on_cooldown(last_unfurl: Option<Instant>, now: Instant) -> bool {
+    match last_unfurl {
+        Some(last_unfurl) => now.duration_since(last_unfurl) < UNFURL_COOLDOWN,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_backloggd_links_returns_review_link() {
+        let actual =
+            find_backloggd_links("check this out https://backloggd.com/u/bodycakes/review/123456/");
+
+        assert_eq!(
+            actual,
+            vec![BackloggdLink::Review {
+                url: "https://backloggd.com/u/bodycakes/review/123456/".to_string(),
+                username: "bodycakes".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn find_backloggd_links_returns_game_and_user_links() {
+        let actual = find_backloggd_links(
+            "https://www.backloggd.com/games/hollow-knight and https://backloggd.com/u/bodycakes/",
+        );
+
+        assert_eq!(
+            actual,
+            vec![
+                BackloggdLink::Game {
+                    url: "https://backloggd.com/games/hollow-knight/".to_string(),
+                },
+                BackloggdLink::User {
+                    url: "https://backloggd.com/u/bodycakes/".to_string(),
+                    username: "bodycakes".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn find_backloggd_links_ignores_sub_pages_and_other_sites() {
+        let actual = find_backloggd_links(
+            "https://backloggd.com/u/bodycakes/games/ https://backloggd.com/u/bodycakes/reviews/rss/ https://example.com/games/hollow-knight/",
+        );
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn find_backloggd_links_ignores_trailing_punctuation() {
+        let actual = find_backloggd_links("(see https://backloggd.com/games/celeste/).");
+
+        assert_eq!(
+            actual,
+            vec![BackloggdLink::Game {
+                url: "https://backloggd.com/games/celeste/".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn find_backloggd_links_removes_duplicates() {
+        let actual = find_backloggd_links(
+            "https://backloggd.com/games/celeste/ https://www.backloggd.com/games/celeste",
+        );
+
+        assert_eq!(actual.len(), 1);
+    }
+
+    #[test]
+    fn find_backloggd_links_limits_links_per_message() {
+        let actual = find_backloggd_links(
+            "https://backloggd.com/games/a/ https://backloggd.com/games/b/ https://backloggd.com/games/c/ https://backloggd.com/games/d/",
+        );
+
+        assert_eq!(actual.len(), MAX_UNFURLS_PER_MESSAGE);
+    }
+
+    #[test]
+    fn is_on_cooldown_returns_true_within_cooldown() {
+        let now = Instant::now();
+
+        assert!(is_on_cooldown(Some(now), now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn is_on_cooldown_returns_false_after_cooldown() {
+        let now = Instant::now();
+
+        assert!(!is_on_cooldown(Some(now), now + UNFURL_COOLDOWN));
+        assert!(!is_on_cooldown(None, now));
+    }
+}
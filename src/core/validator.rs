@@ -44,8 +44,8 @@ mod tests {
     fn validate_feed_url_returns_error_when_username_invalid() {
         let username_too_long = "https:/backloggd.com/u/username-too-loooong/reviews/rss/";
         let username_invalid_characters = "https://backloggd.com/u/username!/reviews/rss/";
-        let actual_too_long = validate_feed_url(&username_too_long);
-        let actual_invalid_char = validate_feed_url(&username_invalid_characters);
+        let actual_too_long = validate_feed_url(username_too_long);
+        let actual_invalid_char = validate_feed_url(username_invalid_characters);
 
         assert!(actual_too_long.is_err());
         assert!(actual_invalid_char.is_err());
@@ -54,7 +54,7 @@ mod tests {
     #[test]
     fn validate_feed_url_returns_error_when_url_malformed() {
         let feed_url_malformed = "https://example.com/u/username-_1/reviews/rss/";
-        let actual = validate_feed_url(&feed_url_malformed);
+        let actual = validate_feed_url(feed_url_malformed);
 
         assert!(actual.is_err());
     }
@@ -1,10 +1,14 @@
 pub mod commands;
 pub mod core;
 
+use core::cache::{CacheConfig, CachingScraper};
+use core::cipher::SecretCipher;
+use core::http::HttpConfig;
 use core::publisher::Publisher;
 use core::repository::{Repository, SqliteRepository};
 use core::scraper::ReqwestScraper;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use crate::core::config;
 use tokio::signal::unix::SignalKind;
@@ -13,7 +17,6 @@ use tokio_util::task::TaskTracker;
 use opentelemetry::global;
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use poise::serenity_prelude as serenity;
-use reqwest::Client;
 use tokio_util::sync::CancellationToken;
 use tracing::error;
 use tracing::info;
@@ -42,16 +45,71 @@ async fn main() {
         format!("Authorization=Basic {}", otlp_auth_header),
     );
 
-    let intents = serenity::GatewayIntents::non_privileged();
+    let cipher = config::get_optional_docker_file_secret("/run/secrets/webhook_key").map(|key| {
+        SecretCipher::from_base64_key(&key).expect("Error reading webhook_key podman secret.")
+    });
+
+    let command_cipher = cipher.clone();
+
+    let scraper = CachingScraper::new(
+        ReqwestScraper::new(HttpConfig::from_env()).expect("Error building HTTP client."),
+        Some(SqliteRepository {}),
+        CacheConfig::default(),
+    );
+    let command_scraper = scraper.clone();
+
+    // Message content is a privileged intent, only needed to find Backloggd links to preview
+    let link_previews = config::get_env_flag("LINK_PREVIEWS");
+    let mut intents = serenity::GatewayIntents::non_privileged();
+    if link_previews {
+        intents |= serenity::GatewayIntents::MESSAGE_CONTENT;
+    }
+
+    let mut options = poise::FrameworkOptions {
+        commands: vec![
+            commands::sub::sub(),
+            commands::backfill::backfill(),
+            commands::preview::preview(),
+            commands::list::list(),
+            commands::digest::digest(),
+            commands::mention::mention(),
+            commands::notify::notify_me(),
+            commands::webhook::webhook(),
+            commands::threads::threads(),
+            commands::settings::settings(),
+            commands::game::game(),
+            commands::user::user(),
+            commands::review::review(),
+            commands::leaderboard::leaderboard(),
+            commands::stats::stats(),
+            commands::recap::recap(),
+            commands::search::search(),
+            commands::random::random(),
+            commands::recommend::recommend(),
+            commands::compare::compare(),
+            commands::link::link(),
+            commands::link::unlink(),
+            commands::user::user_profile(),
+            commands::sub::sub_user(),
+        ],
+        ..Default::default()
+    };
+    if link_previews {
+        options.event_handler = |ctx, event, framework, data| {
+            Box::pin(commands::unfurl::event_handler(ctx, event, framework, data))
+        };
+    }
+
     let framework = poise::Framework::builder()
-        .options(poise::FrameworkOptions {
-            commands: vec![commands::sub::sub(), commands::list::list()],
-            ..Default::default()
-        })
+        .options(options)
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                Ok(commands::Data {})
+                Ok(commands::Data {
+                    cipher: command_cipher,
+                    scraper: command_scraper,
+                    unfurl_cooldowns: Mutex::new(HashMap::new()),
+                })
             })
         })
         .build();
@@ -111,13 +169,17 @@ async fn main() {
     let repo = crate::core::repository::SqliteRepository {};
     repo.init_database().await.unwrap();
 
-    // TODO: Break out into function
-    let client = Client::new();
-    let scraper = ReqwestScraper::new(client);
-
     let context = poise::serenity_prelude::Http::new(&discord_token);
 
-    let publisher = Publisher::new(scraper, SqliteRepository {}, Arc::new(context));
+    if cipher.is_none() {
+        info!("No webhook_key secret found, webhook delivery is disabled.");
+    }
+
+    if !link_previews {
+        info!("LINK_PREVIEWS is not enabled, link previews are disabled.");
+    }
+
+    let publisher = Publisher::new(scraper, SqliteRepository {}, Arc::new(context), cipher);
 
     let token = CancellationToken::new();
     let local_token = token.clone();